@@ -0,0 +1,40 @@
+//! Compare compiled plural form evaluation with the tree-walking interpreter.
+//!
+//! Run with `cargo bench --bench plural`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use polib::plural::CatalogPluralRules;
+
+const ITERATIONS: u64 = 1_000_000;
+
+fn measure(evaluate: impl Fn(u64) -> usize) -> Duration {
+    let start = Instant::now();
+    let mut sum = 0;
+    for n in 0..ITERATIONS {
+        sum += evaluate(black_box(n));
+    }
+    black_box(sum);
+    start.elapsed()
+}
+
+fn main() {
+    let cases = [
+        ("standard (n != 1)", "nplurals=2; plural=(n != 1);"),
+        ("standard Russian", "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);"),
+        ("standard Arabic", "nplurals=6; plural=n==0 ? 0 : n==1 ? 1 : n==2 ? 2 : n%100>=3 && n%100<=10 ? 3 : n%100>=11 ? 4 : 5;"),
+        ("custom bytecode", "nplurals=4; plural=n%100==1 ? 0 : n%100==2 ? 1 : n%100>=3 && n%100<=4 ? 2 : 3;"),
+    ];
+    for (name, rules) in cases {
+        let rules = CatalogPluralRules::parse(rules).unwrap();
+        let compiled = measure(|n| rules.evaluate(n));
+        let interpreted = measure(|n| rules.evaluate_interpreted(n));
+        println!(
+            "{:<20} compiled {:>8.2} ns/op, interpreted {:>8.2} ns/op",
+            name,
+            compiled.as_nanos() as f64 / ITERATIONS as f64,
+            interpreted.as_nanos() as f64 / ITERATIONS as f64,
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use polib::mo_file;
+use polib::mo_file::{self, MOCompileOptions};
 use std::env;
 use std::error::Error;
 use std::path::Path;
@@ -11,6 +11,12 @@ fn main() -> Result<(), Box<dyn Error>> {
             return Ok(());
         }
     };
-    mo_file::compile_from_po(Path::new(&input), Path::new(&output))?;
+    let options = MOCompileOptions {
+        use_fuzzy: false,
+        check: true,
+    };
+    let statistics =
+        mo_file::compile_from_po_with_option(Path::new(&input), Path::new(&output), &options)?;
+    println!("{}", statistics);
     Ok(())
 }
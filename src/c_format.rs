@@ -0,0 +1,242 @@
+//! Parse `printf`-style format directives in messages flagged `c-format`.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// A single format directive, e.g. `%5.2f`, `%2$s` or `%<PRIu64>`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CFormatDirective {
+    /// Explicit argument number given by the `%n$` syntax.
+    pub(crate) argument: Option<usize>,
+    /// Length modifier and conversion specifier, e.g. `d`, `lld` or `<PRIu64>`,
+    /// or `*` for a width or precision taken from an argument.
+    pub(crate) conversion: String,
+    /// Byte range of an ISO C 99 `<PRI...>` placeholder in the string, if any.
+    pub(crate) sysdep: Option<Range<usize>>,
+}
+
+/// Error when a string is not a valid C format string.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CFormatError {
+    pub(crate) message: String,
+}
+
+impl std::fmt::Display for CFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CFormatError {}
+
+const FLAGS: &[u8] = b"-+ #0'I";
+const LENGTH_MODIFIERS: &[u8] = b"hlLqjzt";
+const CONVERSIONS: &[u8] = b"diouxXeEfFgGaAcspnCS";
+
+fn skip_digits(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+    i
+}
+
+/// Parse an argument number followed by `$` at byte i, if any.
+/// Returns the argument number and the index after the `$`.
+fn parse_argument(s: &str, i: usize, start: usize) -> Result<(Option<usize>, usize), CFormatError> {
+    let bytes = s.as_bytes();
+    let digits_end = skip_digits(bytes, i);
+    if digits_end == i || digits_end >= bytes.len() || bytes[digits_end] != b'$' {
+        return Ok((None, i));
+    }
+    match s[i..digits_end].parse() {
+        Ok(argument) if argument > 0 => Ok((Some(argument), digits_end + 1)),
+        _ => Err(CFormatError {
+            message: format!("invalid argument number in directive at byte {}", start),
+        }),
+    }
+}
+
+/// Parse a width or precision at byte i. A `*` takes it from an argument,
+/// which is added to the directives.
+fn parse_width(
+    s: &str,
+    i: usize,
+    start: usize,
+    result: &mut Vec<CFormatDirective>,
+) -> Result<usize, CFormatError> {
+    let bytes = s.as_bytes();
+    if i < bytes.len() && bytes[i] == b'*' {
+        let (argument, i) = parse_argument(s, i + 1, start)?;
+        result.push(CFormatDirective {
+            argument,
+            conversion: String::from("*"),
+            sysdep: None,
+        });
+        Ok(i)
+    } else {
+        Ok(skip_digits(bytes, i))
+    }
+}
+
+/// Parse all format directives in a string. `%%` and `%m` do not consume an argument
+/// and are skipped.
+pub(crate) fn parse_directives(s: &str) -> Result<Vec<CFormatDirective>, CFormatError> {
+    let bytes = s.as_bytes();
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        if i < bytes.len() && bytes[i] == b'%' {
+            i += 1;
+            continue;
+        }
+
+        let (argument, next) = parse_argument(s, i, start)?;
+        i = next;
+        while i < bytes.len() && FLAGS.contains(&bytes[i]) {
+            i += 1;
+        }
+        i = parse_width(s, i, start, &mut result)?;
+        if i < bytes.len() && bytes[i] == b'.' {
+            i = parse_width(s, i + 1, start, &mut result)?;
+        }
+
+        let conversion_start = i;
+        while i < bytes.len() && LENGTH_MODIFIERS.contains(&bytes[i]) {
+            i += 1;
+        }
+        if i < bytes.len() && bytes[i] == b'<' {
+            let end = match s[i..].find('>') {
+                Some(end) => i + end,
+                None => {
+                    return Err(CFormatError {
+                        message: format!("unterminated placeholder in directive {}", &s[start..]),
+                    });
+                }
+            };
+            let name = &s[i + 1..end];
+            if !name.starts_with("PRI") || !name.bytes().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(CFormatError {
+                    message: format!("unknown placeholder <{}>", name),
+                });
+            }
+            result.push(CFormatDirective {
+                argument,
+                conversion: s[conversion_start..end + 1].to_string(),
+                sysdep: Some(i..end + 1),
+            });
+            i = end + 1;
+        } else if i < bytes.len() && bytes[i] == b'm' {
+            i += 1;
+        } else if i < bytes.len() && CONVERSIONS.contains(&bytes[i]) {
+            i += 1;
+            result.push(CFormatDirective {
+                argument,
+                conversion: s[conversion_start..i].to_string(),
+                sysdep: None,
+            });
+        } else {
+            return Err(CFormatError {
+                message: format!("invalid directive at byte {}", start),
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// Map each argument number of a format string to its conversion.
+fn arguments(directives: &[CFormatDirective]) -> BTreeMap<usize, &str> {
+    let mut next = 1;
+    let mut result = BTreeMap::new();
+    for directive in directives {
+        let argument = directive.argument.unwrap_or(next);
+        next = argument.saturating_add(1);
+        result.insert(argument, directive.conversion.as_str());
+    }
+    result
+}
+
+/// Check that a translation consumes the same arguments as the original string.
+/// If `allow_omission` is true, the translation may leave out some arguments,
+/// which is common for plural forms that spell out the number.
+pub(crate) fn check_compatible(
+    original: &str,
+    translation: &str,
+    allow_omission: bool,
+) -> Result<(), CFormatError> {
+    let original = parse_directives(original).map_err(|e| CFormatError {
+        message: format!("msgid is not a valid C format string: {}", e),
+    })?;
+    let translation = parse_directives(translation).map_err(|e| CFormatError {
+        message: format!("msgstr is not a valid C format string: {}", e),
+    })?;
+    let original = arguments(&original);
+    let translation = arguments(&translation);
+    for (argument, conversion) in &translation {
+        match original.get(argument) {
+            Some(expected) if expected == conversion => {}
+            Some(expected) => {
+                return Err(CFormatError {
+                    message: format!(
+                        "format specifications for argument {} are not the same: %{} in msgid, %{} in msgstr",
+                        argument, expected, conversion
+                    ),
+                });
+            }
+            None => {
+                return Err(CFormatError {
+                    message: format!(
+                        "msgstr uses argument {} which does not exist in msgid",
+                        argument
+                    ),
+                });
+            }
+        }
+    }
+    if !allow_omission && original.len() != translation.len() {
+        return Err(CFormatError {
+            message: format!(
+                "msgid has {} format specifications but msgstr has {}",
+                original.len(),
+                translation.len()
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_directives() {
+        let directives = parse_directives("%d%% of %2$-5.3s in %<PRIu64> %lld").unwrap();
+        let conversions: Vec<&str> = directives.iter().map(|d| d.conversion.as_str()).collect();
+        assert_eq!(conversions, vec!["d", "s", "<PRIu64>", "lld"]);
+        assert_eq!(directives[1].argument, Some(2));
+        assert_eq!(directives[2].sysdep, Some(21..29));
+        assert!(parse_directives("100%").is_err());
+        assert!(parse_directives("%99999999999999999999999$d").is_err());
+        assert!(parse_directives("%0$d").is_err());
+
+        let directives = parse_directives("%*.*f: %m").unwrap();
+        let conversions: Vec<&str> = directives.iter().map(|d| d.conversion.as_str()).collect();
+        assert_eq!(conversions, vec!["*", "*", "f"]);
+    }
+
+    #[test]
+    fn test_check_compatible() {
+        assert!(check_compatible("%d files in %s", "%2$s: %1$d files", false).is_ok());
+        assert!(check_compatible("%d files", "%s files", false).is_err());
+        assert!(check_compatible("%d files", "files", false).is_err());
+        assert!(check_compatible("%d files", "one file", true).is_ok());
+        assert!(check_compatible("%*d files", "%2$*1$d files", false).is_ok());
+        assert!(check_compatible("%*d files", "%d files", false).is_err());
+    }
+}
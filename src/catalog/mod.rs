@@ -1,14 +1,28 @@
 //! Defines `Catalog` struct and its iterators.
 
 mod iterator;
+mod plural;
 
 use crate::{
     message::CatalogMessageMutView, message::Message, message::MessageKey, message::MessageView,
     metadata::CatalogMetadata,
 };
 pub use iterator::{Iter, IterMut, MessageMutProxy};
+pub use plural::{PluralResizeReport, ResizedMessage};
 use std::collections::btree_map::BTreeMap;
 
+/// Messages of a catalog that share msgctxt and msgid but differ in msgid_plural, which
+/// gettext cannot tell apart at runtime.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MessageIdConflict {
+    /// Context of the messages.
+    pub msgctxt: Option<String>,
+    /// msgid of the messages.
+    pub msgid: String,
+    /// msgid_plural of each message, None for a singular message.
+    pub msgid_plurals: Vec<Option<String>>,
+}
+
 /// `Catalog` struct represents a collection of _Messages_ stored in a `.po` or `.mo` file.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Catalog {
@@ -46,12 +60,12 @@ impl Catalog {
     }
 
     /// Get an iterator over immutable messages in the catalog.
-    pub fn messages(&self) -> Iter {
+    pub fn messages(&self) -> Iter<'_> {
         Iter::begin(self)
     }
 
     /// Get an iterator over messages in the catalog that allows mutating a message in-place.
-    pub fn messages_mut(&mut self) -> IterMut {
+    pub fn messages_mut(&mut self) -> IterMut<'_> {
         IterMut::begin(self)
     }
 
@@ -71,6 +85,60 @@ impl Catalog {
         }
     }
 
+    /// Find all messages in the catalog with msgctxt and msgid fields, whatever their
+    /// msgid_plural field. A singular message comes first.
+    pub fn find_messages_by_msgid<'a>(
+        &'a self,
+        msgctxt: Option<&str>,
+        msgid: &str,
+    ) -> impl Iterator<Item = &'a dyn MessageView> + 'a {
+        let key = MessageKey::gen(msgctxt, msgid, None);
+        self.map
+            .range(key.clone()..)
+            .take_while(move |(k, _)| k.has_msgid_of(&key))
+            .map(|(_, &index)| self.messages[index].as_ref().unwrap() as &dyn MessageView)
+    }
+
+    /// Find a message in the catalog by msgctxt and msgid fields like gettext does, so that
+    /// a plural message is found without knowing its msgid_plural. If several messages match,
+    /// the singular one or else the one with the first msgid_plural is returned.
+    /// Returns None if the message is not found.
+    pub fn find_message_by_msgid(
+        &self,
+        msgctxt: Option<&str>,
+        msgid: &str,
+    ) -> Option<&dyn MessageView> {
+        self.find_messages_by_msgid(msgctxt, msgid).next()
+    }
+
+    /// Report groups of messages sharing msgctxt and msgid fields, such as a singular and
+    /// a plural message with the same msgid, in the order of their msgctxt and msgid.
+    pub fn msgid_conflicts(&self) -> Vec<MessageIdConflict> {
+        let mut conflicts = Vec::new();
+        let mut indices = self.map.values().peekable();
+        while let Some(&index) = indices.next() {
+            let message = self.messages[index].as_ref().unwrap();
+            let key = MessageKey::gen(message.msgctxt(), message.msgid(), None);
+            let mut msgid_plurals = vec![message.msgid_plural().ok().map(String::from)];
+            while let Some(&&next) = indices.peek() {
+                let next = self.messages[next].as_ref().unwrap();
+                if !MessageKey::from(next).has_msgid_of(&key) {
+                    break;
+                }
+                msgid_plurals.push(next.msgid_plural().ok().map(String::from));
+                indices.next();
+            }
+            if msgid_plurals.len() > 1 {
+                conflicts.push(MessageIdConflict {
+                    msgctxt: message.msgctxt().map(String::from),
+                    msgid: String::from(message.msgid()),
+                    msgid_plurals,
+                });
+            }
+        }
+        conflicts
+    }
+
     /// Find a message in the catalog by msgctxt, msgid and msgid_plural fields and get a mutable view.
     /// All three fields have to fully match. Returns None if the message is not found.
     pub fn find_message_mut(
@@ -78,7 +146,7 @@ impl Catalog {
         msgctxt: Option<&str>,
         msgid: &str,
         msgid_plural: Option<&str>,
-    ) -> Option<MessageMutProxy> {
+    ) -> Option<MessageMutProxy<'_>> {
         let key = MessageKey::gen(msgctxt, msgid, msgid_plural);
         if let Some(&index) = self.map.get(&key) {
             Some(MessageMutProxy::at(self, index))
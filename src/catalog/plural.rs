@@ -0,0 +1,126 @@
+//! Keep plural translations consistent with the plural rules of a catalog.
+
+use super::Catalog;
+use crate::message::{MessageMutView, MessageView};
+use crate::plural::CatalogPluralRules;
+
+/// A plural message whose translations were resized by `Catalog::set_plural_rules`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResizedMessage {
+    /// Context of the message.
+    pub msgctxt: Option<String>,
+    /// msgid of the message.
+    pub msgid: String,
+    /// Translations dropped from the end of `msgstr_plural`, empty if it was padded.
+    pub dropped: Vec<String>,
+}
+
+/// Report of the plural messages changed by `Catalog::set_plural_rules`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PluralResizeReport {
+    /// Messages padded with empty translations. Translated ones are marked fuzzy.
+    pub padded: Vec<ResizedMessage>,
+    /// Messages whose surplus translations were dropped.
+    pub truncated: Vec<ResizedMessage>,
+}
+
+impl PluralResizeReport {
+    /// Was no message changed?
+    pub fn is_empty(&self) -> bool {
+        self.padded.is_empty() && self.truncated.is_empty()
+    }
+}
+
+impl Catalog {
+    /// Replace the plural rules of the catalog and resize the translations of every plural
+    /// message to the new `nplurals`, see `reconcile_plural_forms`.
+    pub fn set_plural_rules(&mut self, rules: CatalogPluralRules) -> PluralResizeReport {
+        self.metadata.plural_rules = rules;
+        self.reconcile_plural_forms()
+    }
+
+    /// Resize the translations of every plural message to `nplurals` of the catalog.
+    /// Missing translations are added as empty strings and the message is marked fuzzy
+    /// if it was translated; surplus translations are dropped and listed in the report.
+    pub fn reconcile_plural_forms(&mut self) -> PluralResizeReport {
+        let nplurals = self.metadata.plural_rules.nplurals;
+        let mut report = PluralResizeReport::default();
+        for mut message in self.messages_mut() {
+            if !message.is_plural() {
+                continue;
+            }
+            let msgstr_plural = message.msgstr_plural_mut().unwrap();
+            let len = msgstr_plural.len();
+            if len == nplurals {
+                continue;
+            }
+            let translated = msgstr_plural.iter().any(|msgstr| !msgstr.is_empty());
+            let dropped = if len > nplurals {
+                msgstr_plural.split_off(nplurals)
+            } else {
+                msgstr_plural.resize(nplurals, String::new());
+                Vec::new()
+            };
+            let resized = ResizedMessage {
+                msgctxt: message.msgctxt().map(String::from),
+                msgid: String::from(message.msgid()),
+                dropped,
+            };
+            if len > nplurals {
+                report.truncated.push(resized);
+            } else {
+                if translated {
+                    message.flags_mut().add_flag("fuzzy");
+                }
+                report.padded.push(resized);
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn test_set_plural_rules() {
+        let mut catalog = Catalog::empty();
+        catalog.metadata.plural_rules =
+            CatalogPluralRules::parse("nplurals=2; plural=(n != 1);").unwrap();
+        for (msgid, msgstr_plural) in [("file", vec!["plik", "pliki"]), ("dir", vec!["", ""])] {
+            catalog.append_or_update(
+                Message::build_plural()
+                    .with_msgid(String::from(msgid))
+                    .with_msgid_plural(format!("{}s", msgid))
+                    .with_msgstr_plural(msgstr_plural.into_iter().map(String::from).collect())
+                    .done(),
+            );
+        }
+
+        let polish = CatalogPluralRules::for_language("pl").unwrap();
+        let report = catalog.set_plural_rules(polish.clone());
+        assert_eq!(report.padded.len(), 2);
+        assert!(report.truncated.is_empty());
+        let file = catalog.find_message(None, "file", Some("files")).unwrap();
+        assert_eq!(file.msgstr_plural().unwrap(), &vec!["plik", "pliki", ""]);
+        assert!(file.is_fuzzy());
+        assert!(!catalog
+            .find_message(None, "dir", Some("dirs"))
+            .unwrap()
+            .is_fuzzy());
+        assert!(catalog.set_plural_rules(polish).is_empty());
+
+        let report = catalog.set_plural_rules(CatalogPluralRules::for_language("ja").unwrap());
+        assert_eq!(
+            report.truncated[0],
+            ResizedMessage {
+                msgctxt: None,
+                msgid: String::from("file"),
+                dropped: vec![String::from("pliki"), String::new()],
+            }
+        );
+        assert_eq!(catalog.metadata.plural_rules.nplurals, 1);
+    }
+}
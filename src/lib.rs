@@ -11,14 +11,19 @@
 //! _Metadata_ is the "header" section of a _Catalog_ that declares its
 //! properties like target language, character encoding, translation template
 //! creation date, last time translated, plural forms rules, etc.
+//!
+//! A _Translator_ looks up translations in a _Catalog_ at runtime like the
+//! `gettext` family of functions.
 
 #![warn(missing_docs)]
 
 extern crate concat_string;
 
+mod c_format;
 pub mod catalog;
 pub mod message;
 pub mod metadata;
 pub mod mo_file;
-mod plural;
+pub mod plural;
 pub mod po_file;
+pub mod translator;
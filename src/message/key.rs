@@ -25,6 +25,14 @@ impl MessageKey {
             },
         }
     }
+
+    /// Does this key belong to a message with the msgctxt and msgid of `key`, a key
+    /// generated without msgid_plural?
+    pub(crate) fn has_msgid_of(&self, key: &MessageKey) -> bool {
+        self.key
+            .strip_prefix(key.key.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('\u{0000}'))
+    }
 }
 
 impl From<&Message> for MessageKey {
@@ -58,4 +66,13 @@ mod test {
             .done();
         assert_eq!("CTXT\u{0004}ID", MessageKey::from(&message).key);
     }
+
+    #[test]
+    fn test_message_key_has_msgid_of() {
+        let key = MessageKey::gen(None, "Book", None);
+        assert!(MessageKey::gen(None, "Book", None).has_msgid_of(&key));
+        assert!(MessageKey::gen(None, "Book", Some("Books")).has_msgid_of(&key));
+        assert!(!MessageKey::gen(None, "Books", None).has_msgid_of(&key));
+        assert!(!MessageKey::gen(Some("shelf"), "Book", None).has_msgid_of(&key));
+    }
 }
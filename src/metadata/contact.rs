@@ -0,0 +1,156 @@
+//! Defines `Contact` struct and accessors of contact fields.
+
+use std::fmt::{Display, Formatter};
+
+use super::CatalogMetadata;
+
+/// A person or team in `Last-Translator` or `Language-Team`, like `Jane Doe <jane@example.com>`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Contact {
+    /// Name of the person or team, may be empty.
+    pub name: String,
+    /// Email address or URL, e.g. of a mailing list or a team page.
+    pub email_or_url: Option<String>,
+}
+
+/// Placeholders filled in by `xgettext` and `msginit` that do not name anyone.
+const PLACEHOLDERS: [&str; 2] = ["FULL NAME <EMAIL@ADDRESS>", "LANGUAGE <LL@li.org>"];
+
+fn is_address(s: &str) -> bool {
+    s.contains('@') || s.contains("://")
+}
+
+impl Contact {
+    /// Create a contact from a name and an optional email address or URL.
+    pub fn new(name: &str, email_or_url: Option<&str>) -> Self {
+        Contact {
+            name: name.to_string(),
+            email_or_url: email_or_url.map(str::to_string),
+        }
+    }
+
+    /// Parse a contact like `Name <email>`, `Name <url>`, `Name (email)`, `Name email` or
+    /// just a name or an address. Returns None if the field is empty or a template placeholder.
+    pub fn parse(contact: &str) -> Option<Self> {
+        let contact = contact.trim();
+        if contact.is_empty() || PLACEHOLDERS.contains(&contact) {
+            return None;
+        }
+        let bracketed = [('<', '>'), ('(', ')')].iter().find_map(|&(open, close)| {
+            let start = contact.rfind(open)?;
+            let address = contact.strip_suffix(close)?[start + 1..].trim();
+            (open == '<' || is_address(address)).then(|| (&contact[..start], address))
+        });
+        let (name, address) = match bracketed {
+            Some(split) => split,
+            None => match contact.rsplit_once(char::is_whitespace) {
+                Some((name, address)) if is_address(address) => (name, address),
+                _ if is_address(contact) && !contact.contains(char::is_whitespace) => ("", contact),
+                _ => (contact, ""),
+            },
+        };
+        let name = name.trim();
+        let name = name
+            .strip_prefix('"')
+            .and_then(|name| name.strip_suffix('"'))
+            .unwrap_or(name);
+        Some(Contact {
+            name: name.to_string(),
+            email_or_url: (!address.is_empty()).then(|| address.to_string()),
+        })
+    }
+}
+
+impl Display for Contact {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.name.is_empty(), &self.email_or_url) {
+            (true, Some(address)) => write!(f, "<{}>", address),
+            (false, Some(address)) => write!(f, "{} <{}>", self.name, address),
+            (_, None) => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl CatalogMetadata {
+    /// Parse `Last-Translator` as a contact.
+    pub fn last_translator_contact(&self) -> Option<Contact> {
+        Contact::parse(&self.last_translator)
+    }
+
+    /// Set `Last-Translator` to a contact, formatted as `Name <email>`.
+    pub fn set_last_translator_contact(&mut self, contact: &Contact) {
+        self.last_translator = contact.to_string();
+    }
+
+    /// Parse `Language-Team` as a contact.
+    pub fn language_team_contact(&self) -> Option<Contact> {
+        Contact::parse(&self.language_team)
+    }
+
+    /// Set `Language-Team` to a contact, formatted as `Name <email or url>`.
+    pub fn set_language_team_contact(&mut self, contact: &Contact) {
+        self.language_team = contact.to_string();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_contact() {
+        let cases = [
+            (
+                "Jane Doe <jane@example.com>",
+                "Jane Doe",
+                Some("jane@example.com"),
+            ),
+            (
+                "French <https://l10n.example.org/fr/>",
+                "French",
+                Some("https://l10n.example.org/fr/"),
+            ),
+            (
+                "\"Doe, Jane\" <jane@example.com>",
+                "Doe, Jane",
+                Some("jane@example.com"),
+            ),
+            (
+                "Jane Doe (jane@example.com)",
+                "Jane Doe",
+                Some("jane@example.com"),
+            ),
+            (
+                "Jane Doe jane@example.com",
+                "Jane Doe",
+                Some("jane@example.com"),
+            ),
+            ("<jane@example.com>", "", Some("jane@example.com")),
+            ("jane@example.com", "", Some("jane@example.com")),
+            ("Jane Doe (Acme)", "Jane Doe (Acme)", None),
+            ("German", "German", None),
+        ];
+        for (input, name, email_or_url) in cases {
+            assert_eq!(
+                Contact::parse(input),
+                Some(Contact::new(name, email_or_url)),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(Contact::parse(""), None);
+        assert_eq!(Contact::parse("FULL NAME <EMAIL@ADDRESS>"), None);
+    }
+
+    #[test]
+    fn test_contact_fields() {
+        let mut metadata = CatalogMetadata::new();
+        metadata.language_team = String::from("LANGUAGE <LL@li.org>");
+        assert_eq!(metadata.language_team_contact(), None);
+
+        metadata.set_last_translator_contact(&Contact::new("Jane Doe", Some("jane@example.com")));
+        assert_eq!(metadata.last_translator, "Jane Doe <jane@example.com>");
+        metadata.set_language_team_contact(&Contact::new("German", None));
+        assert_eq!(metadata.language_team, "German");
+    }
+}
@@ -0,0 +1,142 @@
+//! Defines `CatalogCharset` enum and `Content-Type` accessors.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use super::{CatalogMetadata, MetadataParseError};
+
+/// Character encodings of a catalog that polib can read and write.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum CatalogCharset {
+    /// `UTF-8`
+    #[default]
+    Utf8,
+    /// `ASCII`, which is a subset of UTF-8 that only allows ASCII characters in a catalog.
+    Ascii,
+}
+
+impl CatalogCharset {
+    /// Parse a charset name, case-insensitively. The `CHARSET` placeholder of templates
+    /// is taken as UTF-8.
+    pub fn parse(name: &str) -> Result<Self, MetadataParseError> {
+        match name.to_ascii_uppercase().as_str() {
+            "UTF-8" | "UTF8" | "CHARSET" => Ok(CatalogCharset::Utf8),
+            "ASCII" | "US-ASCII" | "ANSI_X3.4-1968" => Ok(CatalogCharset::Ascii),
+            _ => Err(MetadataParseError::new(&format!(
+                "unsupported charset \"{}\"",
+                name
+            ))),
+        }
+    }
+
+    /// Canonical name of the charset.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CatalogCharset::Utf8 => "UTF-8",
+            CatalogCharset::Ascii => "ASCII",
+        }
+    }
+
+    /// Can the text be written in this charset?
+    pub fn can_encode(&self, text: &str) -> bool {
+        match self {
+            CatalogCharset::Utf8 => true,
+            CatalogCharset::Ascii => text.is_ascii(),
+        }
+    }
+}
+
+impl FromStr for CatalogCharset {
+    type Err = MetadataParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for CatalogCharset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Split a parameter of `Content-Type` like `charset=UTF-8` into key and value.
+fn parameter(parameter: &str) -> Option<(&str, &str)> {
+    let (key, value) = parameter.split_once('=')?;
+    Some((key.trim(), value.trim().trim_matches('"')))
+}
+
+impl CatalogMetadata {
+    /// MIME type in `Content-Type`, e.g. `text/plain`.
+    pub fn mime_type(&self) -> &str {
+        self.content_type.split(';').next().unwrap().trim()
+    }
+
+    /// The raw `charset` parameter in `Content-Type`, if any.
+    pub fn charset_name(&self) -> Option<&str> {
+        self.content_type
+            .split(';')
+            .skip(1)
+            .filter_map(parameter)
+            .find(|(key, _)| key.eq_ignore_ascii_case("charset"))
+            .map(|(_, value)| value)
+    }
+
+    /// Charset declared in `Content-Type`. A catalog without a declared charset is taken
+    /// as UTF-8. Returns an error if the charset is not supported by polib.
+    pub fn charset(&self) -> Result<CatalogCharset, MetadataParseError> {
+        match self.charset_name() {
+            Some(name) => CatalogCharset::parse(name),
+            None => Ok(CatalogCharset::Utf8),
+        }
+    }
+
+    /// Set the `charset` parameter in `Content-Type`, keeping the MIME type and other
+    /// parameters. An empty `Content-Type` becomes `text/plain; charset=...`.
+    pub fn set_charset(&mut self, charset: CatalogCharset) {
+        let mime_type = match self.mime_type() {
+            "" => "text/plain",
+            mime_type => mime_type,
+        };
+        let mut content_type = String::from(mime_type);
+        for p in self.content_type.split(';').skip(1) {
+            match parameter(p) {
+                Some((key, _)) if key.eq_ignore_ascii_case("charset") => {}
+                _ if p.trim().is_empty() => {}
+                _ => {
+                    content_type.push_str("; ");
+                    content_type.push_str(p.trim());
+                }
+            }
+        }
+        content_type.push_str("; charset=");
+        content_type.push_str(charset.name());
+        self.content_type = content_type;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_charset() {
+        let mut metadata = CatalogMetadata::new();
+        assert_eq!(metadata.mime_type(), "");
+        assert_eq!(metadata.charset(), Ok(CatalogCharset::Utf8));
+
+        metadata.content_type = String::from("text/plain; format=flowed; charset=\"us-ascii\"");
+        assert_eq!(metadata.mime_type(), "text/plain");
+        assert_eq!(metadata.charset_name(), Some("us-ascii"));
+        assert_eq!(metadata.charset(), Ok(CatalogCharset::Ascii));
+
+        metadata.set_charset(CatalogCharset::Utf8);
+        assert_eq!(
+            metadata.content_type,
+            "text/plain; format=flowed; charset=UTF-8"
+        );
+
+        metadata.content_type = String::from("text/plain; charset=ISO-8859-1");
+        assert!(metadata.charset().is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Defines `CatalogDateTime` struct.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::MetadataParseError;
+
+/// A timestamp in a catalog header like `POT-Creation-Date` or `PO-Revision-Date`,
+/// stored in the `YYYY-MM-DD HH:MM+ZZZZ` format used by gettext.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CatalogDateTime {
+    /// Year
+    pub year: i32,
+    /// Month, from 1 to 12
+    pub month: u8,
+    /// Day of the month, from 1 to 31
+    pub day: u8,
+    /// Hour, from 0 to 23
+    pub hour: u8,
+    /// Minute, from 0 to 59
+    pub minute: u8,
+    /// Offset from UTC in minutes, e.g. 60 for `+0100`
+    pub utc_offset: i32,
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Number of days from 1970-01-01 to a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = year as i64 - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year as i32, month, day)
+}
+
+fn parse_number(s: &str, digits: usize, what: &str) -> Result<u32, MetadataParseError> {
+    if s.len() != digits || !s.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(MetadataParseError::new(&format!(
+            "invalid {} \"{}\"",
+            what, s
+        )));
+    }
+    Ok(s.parse().unwrap())
+}
+
+impl CatalogDateTime {
+    /// Create a timestamp from a number of seconds since the Unix epoch and
+    /// an offset from UTC in minutes.
+    pub fn from_unix_timestamp(timestamp: i64, utc_offset: i32) -> Self {
+        let local = timestamp.div_euclid(60) + utc_offset as i64;
+        let (year, month, day) = civil_from_days(local.div_euclid(24 * 60));
+        let minute_of_day = local.rem_euclid(24 * 60);
+        CatalogDateTime {
+            year,
+            month,
+            day,
+            hour: (minute_of_day / 60) as u8,
+            minute: (minute_of_day % 60) as u8,
+            utc_offset,
+        }
+    }
+
+    /// Number of seconds since the Unix epoch.
+    pub fn unix_timestamp(&self) -> i64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let minutes = days * 24 * 60 + self.hour as i64 * 60 + self.minute as i64;
+        (minutes - self.utc_offset as i64) * 60
+    }
+
+    /// The current time in UTC.
+    pub fn now() -> Self {
+        let timestamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        };
+        Self::from_unix_timestamp(timestamp, 0)
+    }
+
+    /// Parse a timestamp like `2023-01-01 12:00+0100`.
+    pub fn parse(date: &str) -> Result<Self, MetadataParseError> {
+        let invalid = || MetadataParseError::new(&format!("invalid date \"{}\"", date));
+        let (ymd, time) = date.trim().split_once(' ').ok_or_else(invalid)?;
+        let mut parts = ymd.split('-');
+        let (Some(year), Some(month), Some(day), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let sign_index = time.find(['+', '-']).ok_or_else(invalid)?;
+        let (hm, zone) = time.split_at(sign_index);
+        let (hour, minute) = hm.trim_end().split_once(':').ok_or_else(invalid)?;
+
+        let year = parse_number(year, 4, "year")? as i32;
+        let month = parse_number(month, 2, "month")? as u8;
+        let day = parse_number(day, 2, "day")? as u8;
+        let hour = parse_number(hour, 2, "hour")? as u8;
+        let minute = parse_number(minute, 2, "minute")? as u8;
+        let offset = parse_number(&zone[1..], 4, "time zone")?;
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || offset % 100 > 59
+            || offset > 2400
+        {
+            return Err(invalid());
+        }
+        let utc_offset = (offset / 100 * 60 + offset % 100) as i32;
+        Ok(CatalogDateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            utc_offset: if zone.starts_with('-') {
+                -utc_offset
+            } else {
+                utc_offset
+            },
+        })
+    }
+}
+
+impl FromStr for CatalogDateTime {
+    type Err = MetadataParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for CatalogDateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.utc_offset < 0 { '-' } else { '+' };
+        let offset = self.utc_offset.unsigned_abs();
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}{}{:02}{:02}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            sign,
+            offset / 60,
+            offset % 60
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        let date = CatalogDateTime::parse("2023-02-28 23:45-0330").unwrap();
+        assert_eq!(
+            date,
+            CatalogDateTime {
+                year: 2023,
+                month: 2,
+                day: 28,
+                hour: 23,
+                minute: 45,
+                utc_offset: -210,
+            }
+        );
+        assert_eq!(date.to_string(), "2023-02-28 23:45-0330");
+        assert!(CatalogDateTime::parse("YEAR-MO-DA HO:MI+ZONE").is_err());
+        assert!(CatalogDateTime::parse("2023-02-29 12:00+0000").is_err());
+        assert!(CatalogDateTime::parse("2024-02-29 12:00+0000").is_ok());
+    }
+
+    #[test]
+    fn test_unix_timestamp() {
+        let date = CatalogDateTime::parse("2001-09-09 03:46+0200").unwrap();
+        assert_eq!(date.unix_timestamp(), 1_000_000_000 - 40);
+        assert_eq!(
+            CatalogDateTime::from_unix_timestamp(1_000_000_000, 120).to_string(),
+            "2001-09-09 03:46+0200"
+        );
+        assert_eq!(
+            CatalogDateTime::from_unix_timestamp(-1, 0).to_string(),
+            "1969-12-31 23:59+0000"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Defines `LanguageTag` struct.
+
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{CatalogMetadata, MetadataParseError};
+
+/// A language of a catalog, parsed from a POSIX locale name like `sr_RS@latin` or
+/// a BCP 47 tag like `zh-Hans-CN`. Subtags are normalized to their conventional case.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LanguageTag {
+    /// ISO 639 language subtag in lowercase, e.g. `pt`.
+    pub language: String,
+    /// Script subtag in title case, e.g. `Hans`.
+    pub script: Option<String>,
+    /// Region subtag in uppercase or a UN M.49 code, e.g. `BR` or `419`.
+    pub region: Option<String>,
+    /// POSIX modifier or BCP 47 variant in lowercase, e.g. `valencia`.
+    pub modifier: Option<String>,
+}
+
+fn is_alpha(s: &str, len: std::ops::RangeInclusive<usize>) -> bool {
+    len.contains(&s.len()) && s.bytes().all(|c| c.is_ascii_alphabetic())
+}
+
+fn script_modifier(script: &str) -> Option<&'static str> {
+    match script {
+        "Latn" => Some("latin"),
+        "Cyrl" => Some("cyrillic"),
+        _ => None,
+    }
+}
+
+fn modifier_script(modifier: &str) -> Option<&'static str> {
+    match modifier {
+        "latin" => Some("Latn"),
+        "cyrillic" => Some("Cyrl"),
+        _ => None,
+    }
+}
+
+impl LanguageTag {
+    /// Create a language tag with only a language subtag.
+    pub fn new(language: &str) -> Self {
+        LanguageTag {
+            language: language.to_ascii_lowercase(),
+            ..LanguageTag::default()
+        }
+    }
+
+    /// Parse a POSIX locale name like `pt_BR`, `sr@latin` or `de_DE.UTF-8`, or
+    /// a BCP 47 tag like `pt-BR` or `zh-Hans`. A codeset is accepted and dropped, and
+    /// the `latin` and `cyrillic` modifiers are stored as scripts.
+    pub fn parse(tag: &str) -> Result<Self, MetadataParseError> {
+        let invalid = || MetadataParseError::new(&format!("invalid language tag \"{}\"", tag));
+        let (rest, modifier) = match tag.trim().split_once('@') {
+            Some((rest, modifier)) => (rest, Some(modifier)),
+            None => (tag.trim(), None),
+        };
+        let rest = rest.split_once('.').map_or(rest, |(rest, _codeset)| rest);
+        let mut subtags = rest.split(['_', '-']);
+        let language = subtags.next().unwrap();
+        if !is_alpha(language, 2..=3) {
+            return Err(invalid());
+        }
+        let mut result = LanguageTag::new(language);
+        for subtag in subtags {
+            if result.script.is_none() && result.region.is_none() && is_alpha(subtag, 4..=4) {
+                let mut script = subtag.to_ascii_lowercase();
+                script[..1].make_ascii_uppercase();
+                result.script = Some(script);
+            } else if result.region.is_none()
+                && result.modifier.is_none()
+                && (is_alpha(subtag, 2..=2)
+                    || (subtag.len() == 3 && subtag.bytes().all(|c| c.is_ascii_digit())))
+            {
+                result.region = Some(subtag.to_ascii_uppercase());
+            } else if result.modifier.is_none()
+                && (4..=8).contains(&subtag.len())
+                && subtag.bytes().all(|c| c.is_ascii_alphanumeric())
+            {
+                result.modifier = Some(subtag.to_ascii_lowercase());
+            } else {
+                return Err(invalid());
+            }
+        }
+        if let Some(modifier) = modifier {
+            if result.modifier.is_some() || modifier.is_empty() {
+                return Err(invalid());
+            }
+            let modifier = modifier.to_ascii_lowercase();
+            match modifier_script(&modifier) {
+                Some(script) if result.script.is_none() => result.script = Some(script.to_string()),
+                _ => result.modifier = Some(modifier),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Guess the language from the path of a PO or MO file laid out as `<lang>.po` or
+    /// `<lang>/LC_MESSAGES/<domain>.po`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let parent = path.parent();
+        let name = if parent.and_then(Path::file_name) == Some("LC_MESSAGES".as_ref()) {
+            parent?.parent()?.file_name()?
+        } else {
+            path.file_stem()?
+        };
+        Self::parse(name.to_str()?).ok()
+    }
+
+    /// Format as a POSIX locale name as used in gettext catalogs, e.g. `sr_RS@latin`.
+    /// The Latin and Cyrillic scripts become modifiers, other scripts are kept as a subtag.
+    pub fn to_posix(&self) -> String {
+        let mut result = self.language.clone();
+        let mut modifier = self.modifier.as_deref();
+        if let Some(script) = &self.script {
+            match script_modifier(script) {
+                Some(script_modifier) if modifier.is_none() => modifier = Some(script_modifier),
+                _ => {
+                    result.push('_');
+                    result.push_str(script);
+                }
+            }
+        }
+        if let Some(region) = &self.region {
+            result.push('_');
+            result.push_str(region);
+        }
+        if let Some(modifier) = modifier {
+            result.push('@');
+            result.push_str(modifier);
+        }
+        result
+    }
+
+    /// Tags to look up catalogs for in order of preference, from this tag to the bare
+    /// language, like GNU gettext does: `sr_RS@latin`, `sr@latin`, `sr_RS`, `sr`.
+    pub fn fallbacks(&self) -> Vec<LanguageTag> {
+        let mut result: Vec<LanguageTag> = Vec::new();
+        for keep_variant in [true, false] {
+            for keep_region in [true, false] {
+                let tag = LanguageTag {
+                    language: self.language.clone(),
+                    script: self.script.clone().filter(|_| keep_variant),
+                    region: self.region.clone().filter(|_| keep_region),
+                    modifier: self.modifier.clone().filter(|_| keep_variant),
+                };
+                if !result.contains(&tag) {
+                    result.push(tag);
+                }
+            }
+        }
+        result
+    }
+
+    /// Format as a BCP 47 tag, e.g. `sr-Latn-RS`. Modifiers become variants if they are
+    /// valid as such and are dropped otherwise.
+    pub fn to_bcp47(&self) -> String {
+        let mut result = self.language.clone();
+        if let Some(script) = &self.script {
+            result.push('-');
+            result.push_str(script);
+        }
+        if let Some(region) = &self.region {
+            result.push('-');
+            result.push_str(region);
+        }
+        if let Some(variant) = self.modifier.as_deref().filter(|v| {
+            (5..=8).contains(&v.len()) || (v.len() == 4 && v.as_bytes()[0].is_ascii_digit())
+        }) {
+            result.push('-');
+            result.push_str(variant);
+        }
+        result
+    }
+}
+
+impl FromStr for LanguageTag {
+    type Err = MetadataParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for LanguageTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_posix())
+    }
+}
+
+impl CatalogMetadata {
+    /// Parse `Language` as a language tag.
+    pub fn language_tag(&self) -> Result<LanguageTag, MetadataParseError> {
+        LanguageTag::parse(&self.language)
+    }
+
+    /// Parse `Language` as a language tag, or guess it from the path of the catalog
+    /// if `Language` is missing or invalid.
+    pub fn language_tag_or_from_path(&self, path: &Path) -> Option<LanguageTag> {
+        self.language_tag()
+            .ok()
+            .or_else(|| LanguageTag::from_path(path))
+    }
+
+    /// Set `Language` to a language tag in POSIX form.
+    pub fn set_language_tag(&mut self, tag: &LanguageTag) {
+        self.language = tag.to_posix();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_language_tag() {
+        let cases = [
+            ("pt_BR", "pt_BR", "pt-BR"),
+            ("pt-br", "pt_BR", "pt-BR"),
+            ("zh_Hans", "zh_Hans", "zh-Hans"),
+            ("zh-hant-TW", "zh_Hant_TW", "zh-Hant-TW"),
+            ("sr@latin", "sr@latin", "sr-Latn"),
+            ("sr-Latn-RS", "sr_RS@latin", "sr-Latn-RS"),
+            ("de_DE.UTF-8@euro", "de_DE@euro", "de-DE"),
+            ("ca-ES-valencia", "ca_ES@valencia", "ca-ES-valencia"),
+            ("es_419", "es_419", "es-419"),
+        ];
+        for (input, posix, bcp47) in cases {
+            let tag = LanguageTag::parse(input).unwrap();
+            assert_eq!(tag.to_posix(), posix, "{}", input);
+            assert_eq!(tag.to_bcp47(), bcp47, "{}", input);
+            assert_eq!(LanguageTag::parse(posix).unwrap(), tag, "{}", input);
+        }
+        assert!(LanguageTag::parse("").is_err());
+        assert!(LanguageTag::parse("pt_BR_XX").is_err());
+        assert!(LanguageTag::parse("sr@").is_err());
+    }
+
+    #[test]
+    fn test_language_tag_fallbacks() {
+        let fallbacks = |tag: &str| -> Vec<String> {
+            let tag = LanguageTag::parse(tag).unwrap();
+            tag.fallbacks().iter().map(LanguageTag::to_posix).collect()
+        };
+        assert_eq!(
+            fallbacks("sr-Latn-RS"),
+            vec!["sr_RS@latin", "sr@latin", "sr_RS", "sr"]
+        );
+        assert_eq!(fallbacks("de_AT.UTF-8"), vec!["de_AT", "de"]);
+        assert_eq!(fallbacks("fr"), vec!["fr"]);
+    }
+
+    #[test]
+    fn test_language_tag_from_path() {
+        let from_path = |path: &str| LanguageTag::from_path(Path::new(path)).map(|t| t.to_posix());
+        assert_eq!(from_path("po/pt_BR.po"), Some(String::from("pt_BR")));
+        assert_eq!(
+            from_path("locale/sr@latin/LC_MESSAGES/app.mo"),
+            Some(String::from("sr@latin"))
+        );
+        assert_eq!(from_path("po/messages.pot"), None);
+
+        let metadata = CatalogMetadata::new();
+        assert_eq!(
+            metadata.language_tag_or_from_path(Path::new("po/de.po")),
+            Some(LanguageTag::new("de"))
+        );
+    }
+}
@@ -0,0 +1,405 @@
+//! Defines `CatalogMetadata` struct.
+
+mod contact;
+mod content_type;
+mod date;
+mod language_tag;
+mod validate;
+
+use std::collections::HashMap;
+
+use crate::message::MessageFlags;
+use crate::plural::*;
+
+pub use contact::Contact;
+pub use content_type::CatalogCharset;
+pub use date::CatalogDateTime;
+pub use language_tag::LanguageTag;
+pub use validate::MetadataIssue;
+
+/// Metadata of a translation catalog.
+#[derive(Clone, Debug, Default, Eq)]
+pub struct CatalogMetadata {
+    /// `Project-Id-Version`
+    pub project_id_version: String,
+    /// `POT-Creation-Date`
+    pub pot_creation_date: String,
+    /// `PO-Revision-Date`
+    pub po_revision_date: String,
+    /// `Last-Translator`
+    pub last_translator: String,
+    /// `Language-Team`
+    pub language_team: String,
+    /// `MIME-Version`
+    pub mime_version: String,
+    /// `Content-Type`
+    pub content_type: String,
+    /// `Content-Transfer-Encoding`
+    pub content_transfer_encoding: String,
+    /// `Language`
+    pub language: String,
+    /// `Plural-Forms`
+    pub plural_rules: CatalogPluralRules,
+    /// Header fields without a dedicated member, e.g. `Report-Msgid-Bugs-To` or `X-Generator`,
+    /// in their original order.
+    pub extra_fields: Vec<(String, String)>,
+    /// Keys of header fields in the order they appeared in the parsed header.
+    /// Fields not listed here are exported after them in the default order.
+    /// Not taken into account when comparing metadata.
+    pub field_order: Vec<String>,
+    /// Translator comments above the header entry, e.g. copyright and license lines.
+    pub comments: String,
+    /// Flags of the header entry, e.g. `fuzzy` on a freshly initialized catalog.
+    pub flags: MessageFlags,
+}
+
+impl PartialEq for CatalogMetadata {
+    fn eq(&self, other: &Self) -> bool {
+        self.project_id_version == other.project_id_version
+            && self.pot_creation_date == other.pot_creation_date
+            && self.po_revision_date == other.po_revision_date
+            && self.last_translator == other.last_translator
+            && self.language_team == other.language_team
+            && self.mime_version == other.mime_version
+            && self.content_type == other.content_type
+            && self.content_transfer_encoding == other.content_transfer_encoding
+            && self.language == other.language
+            && self.plural_rules == other.plural_rules
+            && self.extra_fields == other.extra_fields
+            && self.comments == other.comments
+            && self.flags == other.flags
+    }
+}
+
+const KNOWN_FIELDS: [&str; 10] = [
+    "Project-Id-Version",
+    "POT-Creation-Date",
+    "PO-Revision-Date",
+    "Last-Translator",
+    "Language-Team",
+    "MIME-Version",
+    "Content-Type",
+    "Content-Transfer-Encoding",
+    "Language",
+    "Plural-Forms",
+];
+
+/// Error in parsing metadata of a catalog
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetadataParseError {
+    message: String,
+}
+
+impl MetadataParseError {
+    fn new(s: &str) -> Self {
+        Self {
+            message: s.to_string(),
+        }
+    }
+}
+
+impl From<PluralRulesError> for MetadataParseError {
+    fn from(e: PluralRulesError) -> Self {
+        Self {
+            message: e.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for MetadataParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid catalog metadata: {}", self.message)
+    }
+}
+
+impl std::error::Error for MetadataParseError {}
+
+impl CatalogMetadata {
+    /// Create a new empty catalog metadata.
+    pub fn new() -> Self {
+        CatalogMetadata::default()
+    }
+
+    /// Parse catalog metadata from string form stored in PO file.
+    pub fn parse(metadata: &str) -> Result<Self, MetadataParseError> {
+        let mut key_values = HashMap::new();
+        let mut extra_fields: Vec<(String, String)> = Vec::new();
+        let mut field_order: Vec<String> = Vec::new();
+        for line in metadata.split('\n') {
+            if let Some((key, value)) = line.split_once(':') {
+                if !field_order.iter().any(|k| k == key) {
+                    field_order.push(key.to_string());
+                }
+                if KNOWN_FIELDS.contains(&key) {
+                    key_values.insert(key, value.trim());
+                } else if let Some(field) = extra_fields.iter_mut().find(|(k, _)| k == key) {
+                    field.1 = value.trim().to_string();
+                } else {
+                    extra_fields.push((key.to_string(), value.trim().to_string()));
+                }
+            }
+        }
+        let res = CatalogMetadata {
+            project_id_version: key_values
+                .get("Project-Id-Version")
+                .unwrap_or(&"")
+                .to_string(),
+            pot_creation_date: key_values
+                .get("POT-Creation-Date")
+                .unwrap_or(&"")
+                .to_string(),
+            po_revision_date: key_values
+                .get("PO-Revision-Date")
+                .unwrap_or(&"")
+                .to_string(),
+            last_translator: key_values.get("Last-Translator").unwrap_or(&"").to_string(),
+            language_team: key_values.get("Language-Team").unwrap_or(&"").to_string(),
+            mime_version: key_values.get("MIME-Version").unwrap_or(&"").to_string(),
+            content_type: key_values.get("Content-Type").unwrap_or(&"").to_string(),
+            content_transfer_encoding: key_values
+                .get("Content-Transfer-Encoding")
+                .unwrap_or(&"")
+                .to_string(),
+            language: key_values.get("Language").unwrap_or(&"").to_string(),
+            plural_rules: CatalogPluralRules::parse(
+                key_values
+                    .get("Plural-Forms")
+                    .unwrap_or(&"nplurals=1; plural=0;"),
+            )?,
+            extra_fields,
+            field_order,
+            ..CatalogMetadata::default()
+        };
+        Ok(res)
+    }
+
+    /// Value of a header field with a dedicated member.
+    fn known_field(&self, key: &str) -> Option<String> {
+        let value = match key {
+            "Project-Id-Version" => &self.project_id_version,
+            "POT-Creation-Date" => &self.pot_creation_date,
+            "PO-Revision-Date" => &self.po_revision_date,
+            "Last-Translator" => &self.last_translator,
+            "Language-Team" => &self.language_team,
+            "MIME-Version" => &self.mime_version,
+            "Content-Type" => &self.content_type,
+            "Content-Transfer-Encoding" => &self.content_transfer_encoding,
+            "Language" => &self.language,
+            "Plural-Forms" => return Some(self.plural_rules.dump()),
+            _ => return None,
+        };
+        Some(value.clone())
+    }
+
+    fn dump(&self, include_pot_creation_date: bool) -> String {
+        let mut buffer = String::new();
+        let mut written: Vec<&str> = Vec::new();
+        let keys = self
+            .field_order
+            .iter()
+            .map(String::as_str)
+            .chain(KNOWN_FIELDS)
+            .chain(self.extra_fields.iter().map(|(k, _)| k.as_str()));
+        for key in keys {
+            if written.contains(&key) || (key == "POT-Creation-Date" && !include_pot_creation_date)
+            {
+                continue;
+            }
+            written.push(key);
+            let value = match self.known_field(key) {
+                // Keep empty fields that were present in the original header, like `Language: `
+                Some(value) if value.is_empty() && !self.field_order.iter().any(|k| k == key) => {
+                    continue;
+                }
+                Some(value) => value,
+                None => match self.extra_field(key) {
+                    Some(value) => value.to_string(),
+                    None => continue,
+                },
+            };
+            buffer.push_str(format!("{}: {}\n", key, value).as_str());
+        }
+        buffer
+    }
+
+    /// Get the value of a header field without a dedicated member, e.g. `X-Generator`.
+    pub fn extra_field(&self, key: &str) -> Option<&str> {
+        self.extra_fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Set the value of a header field without a dedicated member. An existing field keeps
+    /// its position, a new field is appended after all others.
+    /// Returns an error for fields with a dedicated member, like `Language`, which have to
+    /// be set through that member.
+    pub fn set_extra_field(&mut self, key: &str, value: &str) -> Result<(), MetadataParseError> {
+        if KNOWN_FIELDS.contains(&key) {
+            return Err(MetadataParseError::new(&format!(
+                "{} has a dedicated member",
+                key
+            )));
+        }
+        match self.extra_fields.iter_mut().find(|(k, _)| k == key) {
+            Some(field) => field.1 = value.to_string(),
+            None => self.extra_fields.push((key.to_string(), value.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Remove a header field without a dedicated member and return its value.
+    pub fn remove_extra_field(&mut self, key: &str) -> Option<String> {
+        let index = self.extra_fields.iter().position(|(k, _)| k == key)?;
+        Some(self.extra_fields.remove(index).1)
+    }
+
+    /// Parse `POT-Creation-Date` as a timestamp.
+    pub fn pot_creation_date_time(&self) -> Result<CatalogDateTime, MetadataParseError> {
+        CatalogDateTime::parse(&self.pot_creation_date)
+    }
+
+    /// Set `POT-Creation-Date` to a timestamp.
+    pub fn set_pot_creation_date_time(&mut self, date: CatalogDateTime) {
+        self.pot_creation_date = date.to_string();
+    }
+
+    /// Parse `PO-Revision-Date` as a timestamp.
+    pub fn po_revision_date_time(&self) -> Result<CatalogDateTime, MetadataParseError> {
+        CatalogDateTime::parse(&self.po_revision_date)
+    }
+
+    /// Set `PO-Revision-Date` to a timestamp.
+    pub fn set_po_revision_date_time(&mut self, date: CatalogDateTime) {
+        self.po_revision_date = date.to_string();
+    }
+
+    /// Set `PO-Revision-Date` to the current time in UTC.
+    pub fn touch_revision_date(&mut self) {
+        self.set_po_revision_date_time(CatalogDateTime::now());
+    }
+
+    /// Export metadata for writing to a PO file.
+    pub fn export_for_po(&self) -> String {
+        self.dump(true)
+    }
+
+    /// Export metadata for writing to a MO file.
+    pub fn export_for_mo(&self) -> String {
+        self.dump(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_parse() {
+        let input_data = "Plural-Forms: nplurals=1; plural=0;\n";
+        let res = CatalogMetadata::parse(input_data);
+        let catalog_metadata = res.unwrap();
+        assert_eq!(catalog_metadata.project_id_version, "");
+        assert_eq!(catalog_metadata.pot_creation_date, "");
+        assert_eq!(catalog_metadata.po_revision_date, "");
+        assert_eq!(catalog_metadata.last_translator, "");
+        assert_eq!(catalog_metadata.language_team, "");
+        assert_eq!(catalog_metadata.mime_version, "");
+        assert_eq!(catalog_metadata.content_type, "");
+        assert_eq!(catalog_metadata.content_transfer_encoding, "");
+        assert_eq!(catalog_metadata.language, "");
+    }
+
+    #[test]
+    fn test_metadata_dump() {
+        let input_data = "Plural-Forms: nplurals=1; plural=0;\n";
+        let res = CatalogMetadata::parse(input_data);
+        let catalog_metadata = res.unwrap();
+        let dump_res = catalog_metadata.dump(true);
+        assert_eq!(dump_res, input_data);
+    }
+
+    #[test]
+    fn test_metadata_extra_fields() {
+        let input_data = "Project-Id-Version: foo\nReport-Msgid-Bugs-To: bugs@example.com\nX-Generator: Poedit 3.4\nPlural-Forms: nplurals=1; plural=0;\nX-Team-Project: bar\n";
+        let mut catalog_metadata = CatalogMetadata::parse(input_data).unwrap();
+        assert_eq!(catalog_metadata.project_id_version, "foo");
+        assert_eq!(
+            catalog_metadata.extra_field("X-Generator"),
+            Some("Poedit 3.4")
+        );
+        assert_eq!(catalog_metadata.extra_field("Project-Id-Version"), None);
+        assert_eq!(catalog_metadata.export_for_mo(), input_data);
+
+        catalog_metadata
+            .set_extra_field("X-Generator", "polib")
+            .unwrap();
+        catalog_metadata
+            .set_extra_field("X-Source-Language", "en")
+            .unwrap();
+        assert!(catalog_metadata
+            .set_extra_field("Plural-Forms", "nplurals=2; plural=n != 1;")
+            .is_err());
+        assert!(catalog_metadata.set_extra_field("Language", "de").is_err());
+        assert_eq!(
+            catalog_metadata
+                .export_for_mo()
+                .matches("Plural-Forms")
+                .count(),
+            1
+        );
+        assert_eq!(
+            catalog_metadata.remove_extra_field("Report-Msgid-Bugs-To"),
+            Some(String::from("bugs@example.com"))
+        );
+        let keys: Vec<&str> = catalog_metadata
+            .extra_fields
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["X-Generator", "X-Team-Project", "X-Source-Language"]
+        );
+    }
+
+    #[test]
+    fn test_metadata_dates() {
+        let input_data =
+            "POT-Creation-Date: 2023-01-01 08:00+0100\nPO-Revision-Date: YEAR-MO-DA HO:MI+ZONE\n";
+        let mut catalog_metadata = CatalogMetadata::parse(input_data).unwrap();
+        let created = catalog_metadata.pot_creation_date_time().unwrap();
+        assert_eq!(
+            (created.year, created.hour, created.utc_offset),
+            (2023, 8, 60)
+        );
+        assert!(catalog_metadata.po_revision_date_time().is_err());
+
+        catalog_metadata.touch_revision_date();
+        let revised = catalog_metadata.po_revision_date_time().unwrap();
+        assert_eq!(revised.utc_offset, 0);
+        assert!(revised.unix_timestamp() > created.unix_timestamp());
+    }
+
+    #[test]
+    fn test_metadata_field_order() {
+        let input_data = "Language: \nProject-Id-Version: foo\nPlural-Forms: nplurals=2; plural=(n != 1);\nPOT-Creation-Date: 2023-01-01 00:00+0000\n";
+        let mut catalog_metadata = CatalogMetadata::parse(input_data).unwrap();
+        assert_eq!(catalog_metadata.export_for_po(), input_data);
+        let mut reordered = catalog_metadata.clone();
+        reordered.field_order.reverse();
+        assert_eq!(reordered, catalog_metadata);
+        assert_eq!(
+            catalog_metadata.export_for_mo(),
+            "Language: \nProject-Id-Version: foo\nPlural-Forms: nplurals=2; plural=(n != 1);\n"
+        );
+        catalog_metadata.mime_version = String::from("1.0");
+        catalog_metadata
+            .set_extra_field("X-Generator", "polib")
+            .unwrap();
+        assert_eq!(
+            catalog_metadata.export_for_po(),
+            format!("{}MIME-Version: 1.0\nX-Generator: polib\n", input_data)
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Check catalog metadata like `msgfmt --check-header`.
+
+use std::fmt::{Display, Formatter};
+
+use super::{CatalogDateTime, CatalogMetadata};
+use crate::plural::CatalogPluralRules;
+
+/// A problem found in catalog metadata by `CatalogMetadata::validate`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MetadataIssue {
+    /// A header field is missing or empty.
+    MissingField(&'static str),
+    /// A header field still holds the placeholder filled in by `xgettext` or `msginit`.
+    Placeholder {
+        /// Name of the header field.
+        field: &'static str,
+        /// Value of the header field.
+        value: String,
+    },
+    /// A header field cannot be parsed or holds an unsupported value.
+    InvalidField {
+        /// Name of the header field.
+        field: &'static str,
+        /// Description of the problem.
+        message: String,
+    },
+    /// `Plural-Forms` differs from the known plural rules of `Language`.
+    PluralFormsMismatch {
+        /// The language in `Language`.
+        language: String,
+        /// The known plural rules of the language.
+        expected: String,
+        /// The plural rules in `Plural-Forms`.
+        found: String,
+    },
+}
+
+impl Display for MetadataIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataIssue::MissingField(field) => write!(f, "header field '{}' missing", field),
+            MetadataIssue::Placeholder { field, value } => write!(
+                f,
+                "header field '{}' still has the initial default value '{}'",
+                field, value
+            ),
+            MetadataIssue::InvalidField { field, message } => {
+                write!(f, "header field '{}' is invalid: {}", field, message)
+            }
+            MetadataIssue::PluralFormsMismatch {
+                language,
+                expected,
+                found,
+            } => write!(
+                f,
+                "plural forms '{}' do not match '{}' expected for language '{}'",
+                found, expected, language
+            ),
+        }
+    }
+}
+
+impl CatalogMetadata {
+    /// Check the metadata for missing fields, template placeholders, invalid values and
+    /// a `Plural-Forms` that does not match the known rules of `Language`.
+    /// Returns an empty list if no issue is found.
+    pub fn validate(&self) -> Vec<MetadataIssue> {
+        let mut issues = Vec::new();
+        let text_fields = [
+            (
+                "Project-Id-Version",
+                &self.project_id_version,
+                Some("PACKAGE VERSION"),
+            ),
+            (
+                "PO-Revision-Date",
+                &self.po_revision_date,
+                Some("YEAR-MO-DA HO:MI+ZONE"),
+            ),
+            (
+                "Last-Translator",
+                &self.last_translator,
+                Some("FULL NAME <EMAIL@ADDRESS>"),
+            ),
+            (
+                "Language-Team",
+                &self.language_team,
+                Some("LANGUAGE <LL@li.org>"),
+            ),
+            ("MIME-Version", &self.mime_version, None),
+            (
+                "Content-Type",
+                &self.content_type,
+                Some("text/plain; charset=CHARSET"),
+            ),
+            (
+                "Content-Transfer-Encoding",
+                &self.content_transfer_encoding,
+                Some("ENCODING"),
+            ),
+            ("Language", &self.language, None),
+        ];
+        for (field, value, placeholder) in text_fields {
+            if value.is_empty() {
+                issues.push(MetadataIssue::MissingField(field));
+            } else if placeholder == Some(value.as_str()) {
+                issues.push(MetadataIssue::Placeholder {
+                    field,
+                    value: value.clone(),
+                });
+            }
+        }
+        let mut invalid = |field, message: String| {
+            issues.push(MetadataIssue::InvalidField { field, message });
+        };
+        for (field, value) in [
+            ("POT-Creation-Date", &self.pot_creation_date),
+            ("PO-Revision-Date", &self.po_revision_date),
+        ] {
+            if !value.is_empty() && !value.starts_with("YEAR-MO-DA") {
+                if let Err(e) = CatalogDateTime::parse(value) {
+                    invalid(field, e.to_string());
+                }
+            }
+        }
+        if self.charset_name() != Some("CHARSET") {
+            if let Err(e) = self.charset() {
+                invalid("Content-Type", e.to_string());
+            }
+        }
+        if let Err(e) = self.language_tag() {
+            if !self.language.is_empty() {
+                invalid("Language", e.to_string());
+            }
+        }
+
+        if self.plural_rules == CatalogPluralRules::default()
+            && !self.field_order.iter().any(|k| k == "Plural-Forms")
+        {
+            issues.push(MetadataIssue::MissingField("Plural-Forms"));
+        } else if let Some(expected) = CatalogPluralRules::for_language(&self.language) {
+            if !self.plural_rules.is_equivalent(&expected) {
+                issues.push(MetadataIssue::PluralFormsMismatch {
+                    language: self.language.clone(),
+                    expected: expected.dump(),
+                    found: self.plural_rules.dump(),
+                });
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate_template() {
+        let template = "Project-Id-Version: PACKAGE VERSION\nPOT-Creation-Date: 2023-01-01 00:00+0000\nPO-Revision-Date: YEAR-MO-DA HO:MI+ZONE\nLast-Translator: FULL NAME <EMAIL@ADDRESS>\nLanguage-Team: LANGUAGE <LL@li.org>\nLanguage: \nMIME-Version: 1.0\nContent-Type: text/plain; charset=CHARSET\nContent-Transfer-Encoding: 8bit\n";
+        let issues = CatalogMetadata::parse(template).unwrap().validate();
+        let placeholders: Vec<&str> = issues
+            .iter()
+            .filter_map(|issue| match issue {
+                MetadataIssue::Placeholder { field, .. } => Some(*field),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            placeholders,
+            vec![
+                "Project-Id-Version",
+                "PO-Revision-Date",
+                "Last-Translator",
+                "Language-Team",
+                "Content-Type"
+            ]
+        );
+        assert!(issues.contains(&MetadataIssue::MissingField("Language")));
+        assert!(issues.contains(&MetadataIssue::MissingField("Plural-Forms")));
+        assert_eq!(issues.len(), 7);
+    }
+
+    #[test]
+    fn test_validate_plural_forms() {
+        let header = "Project-Id-Version: foo 1.0\nPO-Revision-Date: 2023-01-01 00:00+0000\nLast-Translator: Jane Doe <jane@example.com>\nLanguage-Team: French <fr@example.com>\nLanguage: fr\nMIME-Version: 1.0\nContent-Type: text/plain; charset=UTF-8\nContent-Transfer-Encoding: 8bit\nPlural-Forms: nplurals=2; plural=n>1;\n";
+        let mut metadata = CatalogMetadata::parse(header).unwrap();
+        assert_eq!(metadata.validate(), vec![]);
+
+        metadata.language = String::from("pt_BR");
+        assert_eq!(metadata.validate(), vec![]);
+        metadata.language = String::from("de");
+        assert_eq!(
+            metadata.validate(),
+            vec![MetadataIssue::PluralFormsMismatch {
+                language: String::from("de"),
+                expected: String::from("nplurals=2; plural=(n != 1);"),
+                found: String::from("nplurals=2; plural=n>1;"),
+            }]
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Compile every PO file in a directory tree.
+
+use super::mo_file_compiler::{compile_po_file, MOCompileOptions, MOCompileStatistics};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Options of compiling every PO file in a directory tree.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MOBatchCompileOptions {
+    /// Options of compiling each PO file.
+    pub compile: MOCompileOptions,
+    /// Directory to write `<lang>/LC_MESSAGES/<domain>.mo` files into.
+    /// If None, MO files are written next to their PO file: `<lang>/LC_MESSAGES/<domain>.mo`
+    /// in the directory holding the `<lang>.po` file or the `<lang>` directory.
+    pub output_dir: Option<PathBuf>,
+    /// Domain of PO files laid out as `<lang>.po`. PO files laid out as
+    /// `<lang>/LC_MESSAGES/<domain>.po` take the domain from their file name instead.
+    pub domain: Option<String>,
+    /// If true, compile PO files even if their MO file is newer.
+    pub force: bool,
+    /// Number of PO files to compile in parallel. Zero means the available parallelism.
+    pub jobs: usize,
+}
+
+impl MOBatchCompileOptions {
+    /// Creates a default MOBatchCompileOptions
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Outcome of compiling a single PO file in a batch.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MOBatchCompileStatus {
+    /// The PO file was compiled.
+    Compiled(MOCompileStatistics),
+    /// The MO file is newer than the PO file and was left alone.
+    UpToDate,
+}
+
+/// Error when the output path of a PO file cannot be determined.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MOBatchLayoutError {
+    message: String,
+}
+
+impl Display for MOBatchLayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for MOBatchLayoutError {}
+
+/// Result of compiling a single PO file in a batch.
+#[derive(Debug)]
+pub struct MOBatchCompileResult {
+    /// Path to the PO file.
+    pub input: PathBuf,
+    /// Path to the MO file, if it can be determined.
+    pub output: Option<PathBuf>,
+    /// Outcome of compiling the PO file, or the error that stopped it.
+    pub status: Result<MOBatchCompileStatus, Box<dyn Error + Send + Sync>>,
+}
+
+fn find_po_files(dir: &Path, result: &mut Vec<PathBuf>) -> Result<(), std::io::Error> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            find_po_files(&path, result)?;
+        } else if path.extension().is_some_and(|extension| extension == "po") {
+            result.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn file_name(path: Option<&Path>) -> Option<&str> {
+    path?.file_name()?.to_str()
+}
+
+/// Work out where the MO file of a PO file goes.
+fn output_path(
+    input: &Path,
+    options: &MOBatchCompileOptions,
+) -> Result<PathBuf, MOBatchLayoutError> {
+    let stem = input.file_stem().and_then(|stem| stem.to_str());
+    let parent = input.parent();
+    let (language, domain, source_dir) = if file_name(parent) == Some("LC_MESSAGES") {
+        let language_dir = parent.and_then(Path::parent);
+        (
+            file_name(language_dir),
+            stem,
+            language_dir.and_then(Path::parent),
+        )
+    } else {
+        (stem, options.domain.as_deref(), parent)
+    };
+    match (language, domain, source_dir) {
+        (Some(language), Some(domain), Some(source_dir)) => {
+            let base = options.output_dir.as_deref().unwrap_or(source_dir);
+            Ok(base
+                .join(language)
+                .join("LC_MESSAGES")
+                .join(format!("{}.mo", domain)))
+        }
+        _ => Err(MOBatchLayoutError {
+            message: format!(
+                "cannot determine language and domain of {}",
+                input.display()
+            ),
+        }),
+    }
+}
+
+fn is_up_to_date(input: &Path, output: &Path) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified());
+    match (modified(input), modified(output)) {
+        (Ok(input), Ok(output)) => output >= input,
+        _ => false,
+    }
+}
+
+fn compile_one(
+    input: &Path,
+    output: &Path,
+    options: &MOBatchCompileOptions,
+) -> Result<MOBatchCompileStatus, Box<dyn Error + Send + Sync>> {
+    if !options.force && is_up_to_date(input, output) {
+        return Ok(MOBatchCompileStatus::UpToDate);
+    }
+    if let Some(dir) = output.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let statistics = compile_po_file(input, output, &options.compile)?;
+    Ok(MOBatchCompileStatus::Compiled(statistics))
+}
+
+/// Compile every `.po` file under a directory into the conventional
+/// `<lang>/LC_MESSAGES/<domain>.mo` layout, in parallel.
+///
+/// Both `<lang>.po` (which needs `MOBatchCompileOptions::domain`) and
+/// `<lang>/LC_MESSAGES/<domain>.po` layouts are recognized. Symbolic links to directories
+/// are not followed. A failure to compile one file does not stop the others; every file
+/// gets its own result, sorted by input path.
+/// Returns an error only if the directory tree cannot be read.
+pub fn compile_directory(
+    root: &Path,
+    options: &MOBatchCompileOptions,
+) -> Result<Vec<MOBatchCompileResult>, std::io::Error> {
+    let mut inputs = Vec::new();
+    find_po_files(root, &mut inputs)?;
+    inputs.sort();
+
+    let jobs = match options.jobs {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        jobs => jobs,
+    };
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<MOBatchCompileResult>>> =
+        Mutex::new(inputs.iter().map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(inputs.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(input) = inputs.get(index) else {
+                    break;
+                };
+                let result = match output_path(input, options) {
+                    Ok(output) => MOBatchCompileResult {
+                        input: input.clone(),
+                        status: compile_one(input, &output, options),
+                        output: Some(output),
+                    },
+                    Err(e) => MOBatchCompileResult {
+                        input: input.clone(),
+                        output: None,
+                        status: Err(Box::new(e)),
+                    },
+                };
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    Ok(results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(Option::unwrap)
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE_PO: &str =
+        "msgid \"\"\nmsgstr \"\"\n\"Language: de\\n\"\n\nmsgid \"Hello\"\nmsgstr \"Hallo\"\n";
+
+    #[test]
+    fn test_compile_directory() {
+        let root = std::env::temp_dir().join(format!("polib-batch-{}", std::process::id()));
+        let nested = root.join("locale").join("fr").join("LC_MESSAGES");
+        std::fs::create_dir_all(root.join("po")).unwrap();
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join("po").join("de.po"), SAMPLE_PO).unwrap();
+        std::fs::write(nested.join("app.po"), SAMPLE_PO).unwrap();
+        std::fs::write(root.join("po").join("broken.po"), "msgid \"x\"\n").unwrap();
+
+        let options = MOBatchCompileOptions {
+            output_dir: Some(root.join("out")),
+            domain: Some(String::from("app")),
+            ..MOBatchCompileOptions::new()
+        };
+        let results = compile_directory(&root, &options).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(root.join("out/de/LC_MESSAGES/app.mo").is_file());
+        assert!(root.join("out/fr/LC_MESSAGES/app.mo").is_file());
+        let failed: Vec<&Path> = results
+            .iter()
+            .filter(|r| r.status.is_err())
+            .map(|r| r.input.as_path())
+            .collect();
+        assert_eq!(failed, vec![root.join("po").join("broken.po")]);
+
+        let results = compile_directory(&root, &options).unwrap();
+        let up_to_date = results
+            .iter()
+            .filter(|r| matches!(r.status, Ok(MOBatchCompileStatus::UpToDate)))
+            .count();
+        assert_eq!(up_to_date, 2);
+
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&root, root.join("po").join("loop")).unwrap();
+        std::fs::remove_dir_all(root.join("out")).unwrap();
+        let options = MOBatchCompileOptions {
+            domain: Some(String::from("app")),
+            ..MOBatchCompileOptions::new()
+        };
+        assert_eq!(compile_directory(&root, &options).unwrap().len(), 3);
+        assert!(root.join("po/de/LC_MESSAGES/app.mo").is_file());
+        assert!(nested.join("app.mo").is_file());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,319 @@
+//! Compile PO files to MO files and back.
+
+use crate::c_format;
+use crate::catalog::Catalog;
+use crate::message::MessageView;
+use crate::po_file;
+use crate::po_file::POParseOptions;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// Options of compiling a PO file to a MO file.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct MOCompileOptions {
+    /// If true, include fuzzy translations in the output, like `msgfmt --use-fuzzy`.
+    pub use_fuzzy: bool,
+    /// If true, check format strings and plural forms of translations, like `msgfmt --check`.
+    pub check: bool,
+}
+
+impl MOCompileOptions {
+    /// Creates a default MOCompileOptions
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Statistics of messages in a compiled catalog, like `msgfmt --statistics`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct MOCompileStatistics {
+    /// Number of translated messages that are not fuzzy.
+    pub translated: usize,
+    /// Number of fuzzy translations.
+    pub fuzzy: usize,
+    /// Number of untranslated messages.
+    pub untranslated: usize,
+}
+
+impl Display for MOCompileStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} translated message{}",
+            self.translated,
+            plural(self.translated)
+        )?;
+        if self.fuzzy > 0 {
+            write!(
+                f,
+                ", {} fuzzy translation{}",
+                self.fuzzy,
+                plural(self.fuzzy)
+            )?;
+        }
+        if self.untranslated > 0 {
+            write!(
+                f,
+                ", {} untranslated message{}",
+                self.untranslated,
+                plural(self.untranslated)
+            )?;
+        }
+        write!(f, ".")
+    }
+}
+
+/// Error when translations do not pass the checks enabled by `MOCompileOptions::check`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MOCompileCheckError {
+    /// Description of every problem found, prefixed with the msgid of the offending message.
+    pub problems: Vec<String>,
+}
+
+impl Display for MOCompileCheckError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "check failed: {}", self.problems.join("; "))
+    }
+}
+
+impl Error for MOCompileCheckError {}
+
+fn check_newlines(msgid: &str, msgstr: &str, field: &str) -> Result<(), String> {
+    if msgstr.is_empty() {
+        return Ok(());
+    }
+    if msgid.starts_with('\n') != msgstr.starts_with('\n') {
+        return Err(format!(
+            "msgid and {} entries do not both begin with '\\n'",
+            field
+        ));
+    }
+    if msgid.ends_with('\n') != msgstr.ends_with('\n') {
+        return Err(format!(
+            "msgid and {} entries do not both end with '\\n'",
+            field
+        ));
+    }
+    Ok(())
+}
+
+fn check_message(message: &dyn MessageView, nplurals: usize) -> Result<(), String> {
+    let is_c_format = message.flags().contains("c-format");
+    if message.is_singular() {
+        let msgstr = message.msgstr().unwrap();
+        check_newlines(message.msgid(), msgstr, "msgstr")?;
+        if is_c_format {
+            c_format::check_compatible(message.msgid(), msgstr, false)
+                .map_err(|e| e.to_string())?;
+        }
+    } else {
+        let msgstr_plural = message.msgstr_plural().unwrap();
+        if msgstr_plural.len() != nplurals {
+            return Err(format!(
+                "number of msgstr[] entries is {} but nplurals is {}",
+                msgstr_plural.len(),
+                nplurals
+            ));
+        }
+        // Like msgfmt, every form takes its arguments from msgid_plural, since form 0
+        // may be used for other numbers than one.
+        let msgid_plural = message.msgid_plural().unwrap();
+        for (i, msgstr) in msgstr_plural.iter().enumerate() {
+            let field = format!("msgstr[{}]", i);
+            let original = if i == 0 {
+                message.msgid()
+            } else {
+                msgid_plural
+            };
+            check_newlines(original, msgstr, &field)?;
+            if is_c_format {
+                c_format::check_compatible(msgid_plural, msgstr, true)
+                    .map_err(|e| format!("{}: {}", field, e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Select the messages of a catalog that go into a MO file according to the compile options.
+/// Returns the selected catalog and statistics over the original catalog.
+pub(crate) fn select_messages(
+    catalog: &Catalog,
+    options: &MOCompileOptions,
+) -> Result<(Catalog, MOCompileStatistics), MOCompileCheckError> {
+    let mut selected = Catalog::new(catalog.metadata.clone());
+    let mut statistics = MOCompileStatistics::default();
+    let mut problems = Vec::new();
+    if options.check {
+        if let Err(e) = catalog.metadata.plural_rules.verify() {
+            problems.push(format!("Plural-Forms: {}", e));
+        }
+    }
+    for message in catalog.messages.iter().flatten() {
+        if !message.is_translated() {
+            statistics.untranslated += 1;
+            continue;
+        }
+        if message.is_fuzzy() {
+            statistics.fuzzy += 1;
+            if !options.use_fuzzy {
+                continue;
+            }
+        } else {
+            statistics.translated += 1;
+        }
+        if options.check {
+            if let Err(problem) = check_message(message, catalog.metadata.plural_rules.nplurals) {
+                problems.push(format!("msgid \"{}\": {}", message.msgid(), problem));
+            }
+        }
+        selected.append_or_update(message.clone());
+    }
+    if problems.is_empty() {
+        Ok((selected, statistics))
+    } else {
+        Err(MOCompileCheckError { problems })
+    }
+}
+
+/// Compile a `.po` file to a `.mo` file with custom compile options.
+/// Untranslated messages are never included in the output.
+/// Returns statistics of the messages in the `.po` file on success.
+pub fn compile_from_po_with_option(
+    input_path: &Path,
+    output_path: &Path,
+    options: &MOCompileOptions,
+) -> Result<MOCompileStatistics, Box<dyn Error>> {
+    compile_po_file(input_path, output_path, options).map_err(|e| e as Box<dyn Error>)
+}
+
+/// Same as `compile_from_po_with_option`, but the error can be sent across threads.
+pub(crate) fn compile_po_file(
+    input_path: &Path,
+    output_path: &Path,
+    options: &MOCompileOptions,
+) -> Result<MOCompileStatistics, Box<dyn Error + Send + Sync>> {
+    let catalog = po_file::parse_with_option(input_path, &POParseOptions::default())?;
+    let (selected, statistics) = select_messages(&catalog, options)?;
+    super::write(&selected, output_path)?;
+    Ok(statistics)
+}
+
+/// Compile a `.po` file to a `.mo` file, leaving out untranslated and fuzzy messages.
+pub fn compile_from_po(input_path: &Path, output_path: &Path) -> Result<(), Box<dyn Error>> {
+    compile_from_po_with_option(input_path, output_path, &MOCompileOptions::default())?;
+    Ok(())
+}
+
+/// Decompile a `.mo` file back to a `.po` file, like `msgunfmt`.
+/// Comments, source locations and flags other than `c-format` are not stored in `.mo` files
+/// and cannot be recovered.
+pub fn decompile_to_po(input_path: &Path, output_path: &Path) -> Result<(), Box<dyn Error>> {
+    let catalog = super::parse(input_path)?;
+    po_file::write_to_file(&catalog, output_path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::message::{Message, MessageMutView};
+    use crate::metadata::CatalogMetadata;
+    use crate::plural::CatalogPluralRules;
+
+    fn sample_catalog() -> Catalog {
+        let mut catalog = Catalog::new(CatalogMetadata::new());
+        catalog.append_or_update(
+            Message::build_singular()
+                .with_msgid(String::from("Hello"))
+                .with_msgstr(String::from("Bonjour"))
+                .done(),
+        );
+        let mut fuzzy = Message::build_singular()
+            .with_msgid(String::from("%d files"))
+            .with_msgstr(String::from("%s fichiers"))
+            .done();
+        fuzzy.flags_mut().add_flag("fuzzy");
+        fuzzy.flags_mut().add_flag("c-format");
+        catalog.append_or_update(fuzzy);
+        catalog.append_or_update(
+            Message::build_singular()
+                .with_msgid(String::from("Untranslated"))
+                .done(),
+        );
+        catalog
+    }
+
+    #[test]
+    fn test_select_messages_skips_fuzzy() {
+        let (selected, statistics) =
+            select_messages(&sample_catalog(), &MOCompileOptions::default()).unwrap();
+        assert_eq!(selected.count(), 1);
+        assert_eq!(
+            statistics,
+            MOCompileStatistics {
+                translated: 1,
+                fuzzy: 1,
+                untranslated: 1
+            }
+        );
+        assert_eq!(
+            statistics.to_string(),
+            "1 translated message, 1 fuzzy translation, 1 untranslated message."
+        );
+    }
+
+    #[test]
+    fn test_select_messages_use_fuzzy_and_check() {
+        let options = MOCompileOptions {
+            use_fuzzy: true,
+            check: false,
+        };
+        let (selected, _) = select_messages(&sample_catalog(), &options).unwrap();
+        assert_eq!(selected.count(), 2);
+
+        let options = MOCompileOptions {
+            use_fuzzy: true,
+            check: true,
+        };
+        let error = select_messages(&sample_catalog(), &options).unwrap_err();
+        assert_eq!(error.problems.len(), 1);
+        assert!(error.problems[0].starts_with("msgid \"%d files\""));
+
+        let mut catalog = sample_catalog();
+        catalog.metadata.plural_rules = CatalogPluralRules::parse("nplurals=1; plural=n;").unwrap();
+        let options = MOCompileOptions {
+            use_fuzzy: false,
+            check: true,
+        };
+        let error = select_messages(&catalog, &options).unwrap_err();
+        assert_eq!(
+            error.problems,
+            vec!["Plural-Forms: plural expression yields 1 for n = 1, which is not below nplurals"]
+        );
+    }
+
+    #[test]
+    fn test_check_message_plural_forms() {
+        let mut message = Message::build_plural()
+            .with_msgid(String::from("one file"))
+            .with_msgid_plural(String::from("%s files\n"))
+            .with_msgstr_plural(vec![
+                String::from("un fichier"),
+                String::from("%s fichiers\n"),
+            ])
+            .done();
+        message.flags_mut().add_flag("c-format");
+        assert_eq!(check_message(&message, 2), Ok(()));
+
+        message.msgstr_plural_mut().unwrap()[0] = String::from("%s fichier");
+        assert_eq!(check_message(&message, 2), Ok(()));
+
+        message.msgstr_plural_mut().unwrap()[0] = String::from("%s fichier de %s");
+        assert!(check_message(&message, 2).is_err());
+        message.msgstr_plural_mut().unwrap()[0] = String::from("un fichier\n");
+        assert!(check_message(&message, 2).is_err());
+    }
+}
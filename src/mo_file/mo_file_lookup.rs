@@ -0,0 +1,280 @@
+//! Look up translations directly from the bytes of a MO file.
+
+use super::mo_file_parser::{MOData, MOParseError};
+use crate::metadata::{CatalogMetadata, MetadataParseError};
+use crate::plural::CatalogPluralRules;
+use std::cmp::Ordering;
+
+/// The hash function used by GNU gettext for the hash table in MO files.
+pub(crate) fn hash_string(s: &[u8]) -> u32 {
+    let mut hval = 0u32;
+    for &c in s {
+        hval = (hval << 4).wrapping_add(c as u32);
+        let g = hval & (0xf << 28);
+        if g != 0 {
+            hval ^= g >> 24;
+            hval ^= g;
+        }
+    }
+    hval
+}
+
+/// The C string prefix of a byte string, i.e. everything before the first NUL.
+fn c_str(s: &[u8]) -> &[u8] {
+    match s.iter().position(|&c| c == 0) {
+        Some(end) => &s[..end],
+        None => s,
+    }
+}
+
+/// A MO file borrowed from memory, e.g. a memory-mapped file, that answers lookups
+/// through its hash table or by binary search without building a `Catalog`.
+///
+/// Only the static string table is searched. Messages stored as system-dependent strings
+/// cannot be returned as borrowed strings and have to be read through `mo_file::parse`.
+#[derive(Clone, Debug)]
+pub struct MOFile<'a> {
+    data: MOData<'a>,
+    num_strings: usize,
+    orig_table_offset: usize,
+    trans_table_offset: usize,
+    hash_table_size: usize,
+    hash_table_offset: usize,
+    plural_rules: CatalogPluralRules,
+}
+
+impl<'a> MOFile<'a> {
+    /// Wrap the bytes of a MO file. Only the header and the metadata entry are validated
+    /// up front, the latter to read the `Plural-Forms` used by `get_plural`.
+    pub fn new(data: &'a [u8]) -> Result<Self, MOParseError> {
+        let data = MOData::new(data)?;
+        let mut mo = Self {
+            data,
+            num_strings: data.usize_at(8)?,
+            orig_table_offset: data.usize_at(12)?,
+            trans_table_offset: data.usize_at(16)?,
+            hash_table_size: data.usize_at(20)?,
+            hash_table_offset: data.usize_at(24)?,
+            plural_rules: CatalogPluralRules::default(),
+        };
+        mo.plural_rules = mo.metadata()?.plural_rules;
+        Ok(mo)
+    }
+
+    /// Number of messages in the static string table, including the metadata entry.
+    pub fn len(&self) -> usize {
+        self.num_strings
+    }
+
+    /// Is the static string table empty?
+    pub fn is_empty(&self) -> bool {
+        self.num_strings == 0
+    }
+
+    /// Parse the metadata stored in the header entry of the MO file.
+    pub fn metadata(&self) -> Result<CatalogMetadata, MetadataParseError> {
+        CatalogMetadata::parse(self.find(b"").unwrap_or(""))
+    }
+
+    /// The plural rules of the metadata, used to select plural forms.
+    pub fn plural_rules(&self) -> &CatalogPluralRules {
+        &self.plural_rules
+    }
+
+    fn original_at(&self, index: usize) -> Option<&'a [u8]> {
+        self.data
+            .string_at(self.orig_table_offset + 8 * index)
+            .ok()
+            .map(c_str)
+    }
+
+    fn translated_at(&self, index: usize) -> Option<&'a str> {
+        let translated = self
+            .data
+            .string_at(self.trans_table_offset + 8 * index)
+            .ok()?;
+        std::str::from_utf8(translated).ok()
+    }
+
+    fn find_by_hash(&self, key: &[u8]) -> Option<usize> {
+        let hval = hash_string(key) as usize;
+        let mut index = hval % self.hash_table_size;
+        let increment = 1 + hval % (self.hash_table_size - 2);
+        for _ in 0..self.hash_table_size {
+            let entry = self
+                .data
+                .usize_at(self.hash_table_offset + 4 * index)
+                .ok()?;
+            if entry == 0 {
+                return None;
+            }
+            if entry <= self.num_strings && self.original_at(entry - 1)? == key {
+                return Some(entry - 1);
+            }
+            if index >= self.hash_table_size - increment {
+                index -= self.hash_table_size - increment;
+            } else {
+                index += increment;
+            }
+        }
+        None
+    }
+
+    fn find_by_binary_search(&self, key: &[u8]) -> Option<usize> {
+        let (mut low, mut high) = (0, self.num_strings);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.original_at(middle)?.cmp(key) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return Some(middle),
+            }
+        }
+        None
+    }
+
+    fn find(&self, key: &[u8]) -> Option<&'a str> {
+        let index = if self.hash_table_size > 2 {
+            self.find_by_hash(key)
+        } else {
+            self.find_by_binary_search(key)
+        }?;
+        self.translated_at(index)
+    }
+
+    fn find_message(&self, msgctxt: Option<&str>, msgid: &str) -> Option<&'a str> {
+        match msgctxt {
+            Some(msgctxt) => {
+                let mut key = Vec::with_capacity(msgctxt.len() + 1 + msgid.len());
+                key.extend_from_slice(msgctxt.as_bytes());
+                key.push(4u8);
+                key.extend_from_slice(msgid.as_bytes());
+                self.find(&key)
+            }
+            None => self.find(msgid.as_bytes()),
+        }
+    }
+
+    /// Get the translation of a message by msgctxt and msgid.
+    /// For a plural message this is the first plural form.
+    /// Returns None if the message is not found.
+    pub fn get(&self, msgctxt: Option<&str>, msgid: &str) -> Option<&'a str> {
+        self.find_message(msgctxt, msgid)
+            .map(|translated| translated.split('\u{0000}').next().unwrap())
+    }
+
+    /// Get a plural form of the translation of a message by msgctxt, msgid and
+    /// the index of the plural form. Returns None if the message or the plural form is not found.
+    pub fn get_plural_form(
+        &self,
+        msgctxt: Option<&str>,
+        msgid: &str,
+        form: usize,
+    ) -> Option<&'a str> {
+        self.find_message(msgctxt, msgid)?
+            .split('\u{0000}')
+            .nth(form)
+    }
+
+    /// Get the translation of a message by msgctxt and msgid in the plural form for n,
+    /// selected by the `Plural-Forms` of the metadata. Like GNU gettext, the first form
+    /// is returned if the translation has fewer forms. Returns None if the message is not found.
+    pub fn get_plural(&self, msgctxt: Option<&str>, msgid: &str, n: u64) -> Option<&'a str> {
+        let translated = self.find_message(msgctxt, msgid)?;
+        let form = self.plural_rules.evaluate(n);
+        let mut forms = translated.split('\u{0000}');
+        let first = forms.next();
+        form.checked_sub(1)
+            .and_then(|skip| forms.nth(skip))
+            .or(first)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::catalog::Catalog;
+    use crate::message::Message;
+    use crate::mo_file::mo_file_writer::serialize;
+    use crate::plural::CatalogPluralRules;
+
+    fn sample_metadata() -> CatalogMetadata {
+        let mut metadata = CatalogMetadata::new();
+        metadata.plural_rules = CatalogPluralRules::for_language("fr").unwrap();
+        metadata
+    }
+
+    fn sample_image() -> Vec<u8> {
+        let mut catalog = Catalog::new(sample_metadata());
+        for i in 0..100 {
+            catalog.append_or_update(
+                Message::build_singular()
+                    .with_msgid(format!("message {}", i))
+                    .with_msgstr(format!("translation {}", i))
+                    .done(),
+            );
+        }
+        catalog.append_or_update(
+            Message::build_plural()
+                .with_msgctxt(String::from("shelf"))
+                .with_msgid(String::from("Book"))
+                .with_msgid_plural(String::from("Books"))
+                .with_msgstr_plural(vec![String::from("Livre"), String::from("Livres")])
+                .done(),
+        );
+        serialize(&catalog)
+    }
+
+    fn check_lookups(mo: &MOFile) {
+        assert_eq!(mo.len(), 102);
+        for i in 0..100 {
+            let translation = format!("translation {}", i);
+            assert_eq!(mo.get(None, &format!("message {}", i)), Some(&*translation));
+        }
+        assert_eq!(mo.get(None, "message 100"), None);
+        assert_eq!(mo.get(None, "Book"), None);
+        assert_eq!(mo.get(Some("shelf"), "Book"), Some("Livre"));
+        assert_eq!(mo.get_plural_form(Some("shelf"), "Book", 1), Some("Livres"));
+        assert_eq!(mo.get_plural_form(Some("shelf"), "Book", 2), None);
+        assert_eq!(mo.get_plural(Some("shelf"), "Book", 1), Some("Livre"));
+        assert_eq!(mo.get_plural(Some("shelf"), "Book", 2), Some("Livres"));
+        assert_eq!(mo.get_plural(None, "message 1", 2), Some("translation 1"));
+        assert_eq!(mo.get_plural(None, "Book", 2), None);
+        assert_eq!(mo.metadata().unwrap(), sample_metadata());
+        assert_eq!(mo.plural_rules(), &sample_metadata().plural_rules);
+    }
+
+    #[test]
+    fn test_hash_string() {
+        assert_eq!(hash_string(b""), 0);
+        assert_eq!(hash_string(b"Hello"), 0x004e_c32f);
+    }
+
+    #[test]
+    fn test_lookup_by_hash() {
+        let image = sample_image();
+        let mo = MOFile::new(&image).unwrap();
+        assert!(mo.hash_table_size > 2);
+        check_lookups(&mo);
+    }
+
+    #[test]
+    fn test_lookup_by_binary_search() {
+        let mut image = sample_image();
+        image[20..24].copy_from_slice(&0u32.to_ne_bytes());
+        let mo = MOFile::new(&image).unwrap();
+        check_lookups(&mo);
+    }
+
+    #[test]
+    fn test_invalid_plural_forms() {
+        let mut image = sample_image();
+        let expr = image
+            .windows(7)
+            .position(|window| window == b"plural=")
+            .unwrap()
+            + 7;
+        image[expr] = b'$';
+        assert!(MOFile::new(&image).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+//! Parse MO files.
+
+use super::mo_file_writer::{MAGIC_NUMBER, SEGMENTS_END};
+use crate::catalog::Catalog;
+use crate::message::{Message, MessageMutView};
+use crate::metadata::{CatalogMetadata, MetadataParseError};
+use std::io::Read;
+use std::path::Path;
+use std::str::Utf8Error;
+
+/// Error in parsing a MO file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MOParseError {
+    message: String,
+}
+
+impl MOParseError {
+    pub(crate) fn new(s: &str) -> Self {
+        Self {
+            message: s.to_string(),
+        }
+    }
+}
+
+impl From<std::io::Error> for MOParseError {
+    fn from(value: std::io::Error) -> Self {
+        Self {
+            message: value.to_string(),
+        }
+    }
+}
+
+impl From<MetadataParseError> for MOParseError {
+    fn from(value: MetadataParseError) -> Self {
+        Self {
+            message: value.to_string(),
+        }
+    }
+}
+
+impl From<Utf8Error> for MOParseError {
+    fn from(value: Utf8Error) -> Self {
+        Self {
+            message: value.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for MOParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "MO parse error: {}", self.message)
+    }
+}
+
+impl std::error::Error for MOParseError {}
+
+/// Bounds-checked access to the raw bytes of a MO file in either byte order.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MOData<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> MOData<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Result<Self, MOParseError> {
+        if data.len() < 28 {
+            return Err(MOParseError::new("file is too short"));
+        }
+        let magic: [u8; 4] = data[0..4].try_into().unwrap();
+        let big_endian = if u32::from_le_bytes(magic) == MAGIC_NUMBER {
+            false
+        } else if u32::from_be_bytes(magic) == MAGIC_NUMBER {
+            true
+        } else {
+            return Err(MOParseError::new("bad magic number"));
+        };
+        let result = Self { data, big_endian };
+        if result.u32_at(4)? >> 16 != 0 {
+            return Err(MOParseError::new("unsupported major revision"));
+        }
+        Ok(result)
+    }
+
+    pub(crate) fn u32_at(&self, offset: usize) -> Result<u32, MOParseError> {
+        let bytes: [u8; 4] = offset
+            .checked_add(4)
+            .and_then(|end| self.data.get(offset..end))
+            .ok_or_else(|| MOParseError::new("offset out of bounds"))?
+            .try_into()
+            .unwrap();
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    pub(crate) fn usize_at(&self, offset: usize) -> Result<usize, MOParseError> {
+        Ok(self.u32_at(offset)? as usize)
+    }
+
+    pub(crate) fn bytes_at(&self, offset: usize, length: usize) -> Result<&'a [u8], MOParseError> {
+        offset
+            .checked_add(length)
+            .and_then(|end| self.data.get(offset..end))
+            .ok_or_else(|| MOParseError::new("string out of bounds"))
+    }
+
+    /// Get the string described by the (length, offset) pair at the given table entry.
+    pub(crate) fn string_at(&self, entry: usize) -> Result<&'a [u8], MOParseError> {
+        let length = self.usize_at(entry)?;
+        let offset = self.usize_at(entry + 4)?;
+        self.bytes_at(offset, length)
+    }
+
+    /// Expand the system-dependent string described at the given offset, writing each
+    /// segment reference back as a `<name>` placeholder.
+    fn sysdep_string_at(
+        &self,
+        descriptor: usize,
+        names: &[&[u8]],
+    ) -> Result<Vec<u8>, MOParseError> {
+        let mut result = Vec::new();
+        let mut offset = self.usize_at(descriptor)?;
+        let mut pair = descriptor + 4;
+        loop {
+            let size = self.usize_at(pair)?;
+            let sysdep_ref = self.u32_at(pair + 4)?;
+            result.extend_from_slice(self.bytes_at(offset, size)?);
+            offset += size;
+            pair += 8;
+            if sysdep_ref == SEGMENTS_END {
+                break;
+            }
+            let name = names
+                .get(sysdep_ref as usize)
+                .ok_or_else(|| MOParseError::new("invalid system-dependent segment"))?;
+            result.push(b'<');
+            result.extend_from_slice(name);
+            result.push(b'>');
+        }
+        if result.last() == Some(&0u8) {
+            result.pop();
+        }
+        Ok(result)
+    }
+}
+
+fn add_message(
+    catalog: &mut Catalog,
+    original: &[u8],
+    translated: &[u8],
+    c_format: bool,
+) -> Result<(), MOParseError> {
+    let original = std::str::from_utf8(original)?;
+    let translated = std::str::from_utf8(translated)?;
+    if original.is_empty() {
+        catalog.metadata = CatalogMetadata::parse(translated)?;
+        return Ok(());
+    }
+    let (msgctxt, msgid) = match original.split_once('\u{0004}') {
+        Some((msgctxt, msgid)) => (msgctxt, msgid),
+        None => ("", original),
+    };
+    let mut message = match msgid.split_once('\u{0000}') {
+        Some((msgid, msgid_plural)) => Message::build_plural()
+            .with_msgctxt(msgctxt.to_string())
+            .with_msgid(msgid.to_string())
+            .with_msgid_plural(msgid_plural.to_string())
+            .with_msgstr_plural(translated.split('\u{0000}').map(String::from).collect())
+            .done(),
+        None => Message::build_singular()
+            .with_msgctxt(msgctxt.to_string())
+            .with_msgid(msgid.to_string())
+            .with_msgstr(translated.to_string())
+            .done(),
+    };
+    if c_format {
+        message.flags_mut().add_flag("c-format");
+    }
+    catalog.append_or_update(message);
+    Ok(())
+}
+
+/// Parse a MO file from bytes in memory and returns a catalog on success.
+pub(crate) fn parse_from_bytes(data: &[u8]) -> Result<Catalog, MOParseError> {
+    let mo = MOData::new(data)?;
+    let mut catalog = Catalog::empty();
+
+    let num_strings = mo.usize_at(8)?;
+    let orig_table_offset = mo.usize_at(12)?;
+    let trans_table_offset = mo.usize_at(16)?;
+    for i in 0..num_strings {
+        let original = mo.string_at(orig_table_offset + 8 * i)?;
+        let translated = mo.string_at(trans_table_offset + 8 * i)?;
+        add_message(&mut catalog, original, translated, false)?;
+    }
+
+    if mo.u32_at(4)? & 0xffff >= 1 {
+        let num_segments = mo.usize_at(28)?;
+        let segment_table_offset = mo.usize_at(32)?;
+        let num_sysdep_strings = mo.usize_at(36)?;
+        let orig_sysdep_table_offset = mo.usize_at(40)?;
+        let trans_sysdep_table_offset = mo.usize_at(44)?;
+        let names = (0..num_segments)
+            .map(|i| {
+                mo.string_at(segment_table_offset + 8 * i)
+                    .map(|name| name.strip_suffix(&[0u8]).unwrap_or(name))
+            })
+            .collect::<Result<Vec<&[u8]>, MOParseError>>()?;
+        for i in 0..num_sysdep_strings {
+            let original =
+                mo.sysdep_string_at(mo.usize_at(orig_sysdep_table_offset + 4 * i)?, &names)?;
+            let translated =
+                mo.sysdep_string_at(mo.usize_at(trans_sysdep_table_offset + 4 * i)?, &names)?;
+            add_message(&mut catalog, &original, &translated, true)?;
+        }
+    }
+
+    Ok(catalog)
+}
+
+/// Parse a MO file and returns a catalog on success.
+/// Messages stored as system-dependent strings get their `<PRI...>` placeholders
+/// restored and are flagged `c-format`.
+pub fn parse_from_reader<R: Read>(mut read: R) -> Result<Catalog, MOParseError> {
+    let mut data = Vec::new();
+    read.read_to_end(&mut data)?;
+    parse_from_bytes(&data)
+}
+
+/// Parse a MO file and returns a catalog on success.
+pub fn parse(path: &Path) -> Result<Catalog, MOParseError> {
+    let data = std::fs::read(path)?;
+    parse_from_bytes(&data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::message::MessageView;
+    use crate::mo_file::mo_file_writer::serialize;
+
+    #[test]
+    fn test_sysdep_round_trip() {
+        let mut catalog = Catalog::new(CatalogMetadata::new());
+        catalog.append_or_update(
+            Message::build_singular()
+                .with_msgid(String::from("Hello"))
+                .with_msgstr(String::from("Bonjour"))
+                .done(),
+        );
+        let mut sysdep = Message::build_plural()
+            .with_msgctxt(String::from("copy"))
+            .with_msgid(String::from("%<PRIu64> byte"))
+            .with_msgid_plural(String::from("%<PRIu64> bytes"))
+            .with_msgstr_plural(vec![
+                String::from("%<PRIu64> octet"),
+                String::from("%<PRIu64> octets"),
+            ])
+            .done();
+        sysdep.flags_mut().add_flag("c-format");
+        catalog.append_or_update(sysdep.clone());
+
+        let image = serialize(&catalog);
+        let mo = MOData::new(&image).unwrap();
+        assert_eq!(mo.u32_at(4).unwrap(), 1);
+        assert_eq!(mo.usize_at(8).unwrap(), 2);
+        assert_eq!(mo.usize_at(28).unwrap(), 1);
+        assert_eq!(mo.usize_at(36).unwrap(), 1);
+
+        let parsed = parse_from_bytes(&image).unwrap();
+        assert_eq!(parsed.count(), 2);
+        let message = parsed
+            .find_message(Some("copy"), "%<PRIu64> byte", Some("%<PRIu64> bytes"))
+            .unwrap();
+        assert!(message.flags().contains("c-format"));
+        assert_eq!(
+            message.msgstr_plural().unwrap(),
+            sysdep.msgstr_plural().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_static_strings_keep_revision_zero() {
+        let mut catalog = Catalog::new(CatalogMetadata::new());
+        catalog.append_or_update(
+            Message::build_singular()
+                .with_msgid(String::from("%<PRIu64> files"))
+                .with_msgstr(String::from("%<PRIu64> fichiers"))
+                .done(),
+        );
+        let image = serialize(&catalog);
+        assert_eq!(MOData::new(&image).unwrap().u32_at(4).unwrap(), 0);
+        assert_eq!(parse_from_bytes(&image).unwrap(), catalog);
+    }
+}
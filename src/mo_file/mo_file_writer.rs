@@ -1,126 +1,274 @@
 //! Write MO files.
 
 use std::{
+    collections::HashMap,
     fs::File,
     io::{BufWriter, Write},
     path::Path,
 };
 
-use crate::{catalog::Catalog, message::MessageView};
+use super::mo_file_lookup::hash_string;
+use crate::{c_format, catalog::Catalog, message::MessageView};
 
-fn original_repr_len(message: &dyn MessageView) -> usize {
-    let mut result = 0usize;
-    if let Some(ctxt) = message.msgctxt() {
-        result += ctxt.len() + 1;
-    }
-    result += message.msgid().len();
-    if message.is_plural() {
-        result += 1 + message.msgid_plural().unwrap().len();
-    }
-    result
+pub(crate) const MAGIC_NUMBER: u32 = 0x950412de;
+pub(crate) const SEGMENTS_END: u32 = 0xffffffff;
+
+/// A piece of a string followed by an optional system-dependent segment, e.g. `PRIu64`.
+struct Segment {
+    text: Vec<u8>,
+    sysdep: Option<String>,
 }
 
-fn write_original_repr(
-    writer: &mut BufWriter<std::fs::File>,
-    message: &dyn MessageView,
-) -> Result<(), std::io::Error> {
-    if let Some(ctxt) = message.msgctxt() {
-        writer.write_all(ctxt.as_bytes())?;
-        writer.write_all(&[4u8])?;
+/// Split a string into segments around the `<PRI...>` placeholders of its format directives.
+/// `prefix` is copied verbatim, `parts` are joined with NUL and are scanned for placeholders
+/// only if `c_format` is true.
+fn split_segments(prefix: &str, parts: &[&str], c_format: bool) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut current = prefix.as_bytes().to_vec();
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            current.push(0u8);
+        }
+        let mut last = 0;
+        if c_format {
+            for directive in c_format::parse_directives(part).unwrap_or_default() {
+                if let Some(range) = directive.sysdep {
+                    current.extend_from_slice(&part.as_bytes()[last..range.start]);
+                    segments.push(Segment {
+                        text: std::mem::take(&mut current),
+                        sysdep: Some(part[range.start + 1..range.end - 1].to_string()),
+                    });
+                    last = range.end;
+                }
+            }
+        }
+        current.extend_from_slice(&part.as_bytes()[last..]);
     }
-    writer.write_all(message.msgid().as_bytes())?;
+    segments.push(Segment {
+        text: current,
+        sysdep: None,
+    });
+    segments
+}
+
+fn original_segments(message: &dyn MessageView) -> Vec<Segment> {
+    let prefix = match message.msgctxt() {
+        Some(ctxt) => format!("{}\u{0004}", ctxt),
+        None => String::new(),
+    };
+    let c_format = message.flags().contains("c-format");
+    let mut segments = split_segments(&prefix, &[message.msgid()], c_format);
     if message.is_plural() {
-        writer.write_all(&[0u8])?;
-        writer.write_all(message.msgid_plural().unwrap().as_bytes())?;
+        // Like msgfmt, leave msgid_plural verbatim as the runtime never looks it up
+        let last = segments.last_mut().unwrap();
+        last.text.push(0u8);
+        last.text
+            .extend_from_slice(message.msgid_plural().unwrap().as_bytes());
     }
-    Ok(())
+    segments
 }
 
-fn translated_repr_len(message: &dyn MessageView) -> usize {
-    if let true = message.is_singular() {
-        message.msgstr().unwrap().len()
-    } else {
-        message
+fn translated_segments(message: &dyn MessageView) -> Vec<Segment> {
+    let c_format = message.flags().contains("c-format");
+    if message.is_plural() {
+        let plurals: Vec<&str> = message
             .msgstr_plural()
             .unwrap()
             .iter()
-            .map(|x| x.len() + 1)
-            .sum::<usize>()
-            - 1
+            .map(String::as_str)
+            .collect();
+        split_segments("", &plurals, c_format)
+    } else {
+        split_segments("", &[message.msgstr().unwrap()], c_format)
     }
 }
 
-fn write_translated_repr(
-    writer: &mut BufWriter<std::fs::File>,
-    message: &dyn MessageView,
-) -> Result<(), std::io::Error> {
-    if message.is_singular() {
-        writer.write_all(message.msgstr().unwrap().as_bytes())?;
-    } else {
-        writer.write_all(message.msgstr_plural().unwrap().join("\u{0000}").as_bytes())?;
+/// Size of the hash table as chosen by msgfmt, the smallest prime not less than 4/3 of
+/// the number of strings.
+// `usize::is_multiple_of` would require Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+fn hash_table_size(num_strings: usize) -> usize {
+    let is_prime = |n: usize| n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| n % d != 0);
+    let mut size = std::cmp::max(3, num_strings * 4 / 3);
+    while !is_prime(size) {
+        size += 1;
     }
-    Ok(())
+    size
 }
 
-/// Saves a catalog to a binary MO file.
-pub fn write(catalog: &Catalog, path: &Path) -> Result<(), std::io::Error> {
-    let file = File::create(path)?;
-    let mut writer = BufWriter::new(file);
+fn put_u32(image: &mut [u8], at: usize, value: usize) {
+    image[at..at + 4].copy_from_slice(&(value as u32).to_ne_bytes());
+}
 
-    let metadata = catalog.metadata.export_for_mo();
+fn reserve(image: &mut Vec<u8>, size: usize) -> usize {
+    let offset = image.len();
+    image.resize(offset + size, 0u8);
+    offset
+}
 
-    // Header
-    let magic_number: u32 = 0x950412de;
-    let format_ver: u32 = 0x00000000;
-    let num_strings = catalog.count() + 1;
-    let orig_table_offset = 28;
-    let trans_table_offset = orig_table_offset + 8 * num_strings;
-    writer.write_all(&magic_number.to_ne_bytes())?;
-    writer.write_all(&format_ver.to_ne_bytes())?;
-    writer.write_all(&(num_strings as u32).to_ne_bytes())?;
-    writer.write_all(&(orig_table_offset as u32).to_ne_bytes())?;
-    writer.write_all(&(trans_table_offset as u32).to_ne_bytes())?;
-    writer.write_all(&[0u8; 4])?;
-    writer.write_all(&[0u8; 4])?;
-
-    // O table
-    let mut offset = trans_table_offset + 8 * num_strings;
-    writer.write_all(&0u32.to_ne_bytes())?;
-    writer.write_all(&(offset as u32).to_ne_bytes())?;
-    offset += 1;
+/// Serializes a catalog to the binary MO format.
+///
+/// Messages flagged `c-format` whose format directives use ISO C 99 `<PRI...>` placeholders
+/// are stored as system-dependent strings, which bumps the file format to minor revision 1.
+pub(crate) fn serialize(catalog: &Catalog) -> Vec<u8> {
+    let mut static_strings = vec![(
+        vec![Segment {
+            text: vec![],
+            sysdep: None,
+        }],
+        vec![Segment {
+            text: catalog.metadata.export_for_mo().into_bytes(),
+            sysdep: None,
+        }],
+    )];
+    let mut sysdep_strings = Vec::new();
     for &index in catalog.map.values() {
-        let length = original_repr_len(catalog.messages[index].as_ref().unwrap());
-        writer.write_all(&(length as u32).to_ne_bytes())?;
-        writer.write_all(&(offset as u32).to_ne_bytes())?;
-        offset += length + 1;
+        let message = catalog.messages[index].as_ref().unwrap();
+        let original = original_segments(message);
+        let translated = translated_segments(message);
+        if original.len() > 1 || translated.len() > 1 {
+            sysdep_strings.push((original, translated));
+        } else {
+            static_strings.push((original, translated));
+        }
     }
 
-    // T table
-    writer.write_all(&(metadata.len() as u32).to_ne_bytes())?;
-    writer.write_all(&(offset as u32).to_ne_bytes())?;
-    offset += metadata.len() + 1;
-    for &index in catalog.map.values() {
-        let length = translated_repr_len(catalog.messages[index].as_ref().unwrap());
-        writer.write_all(&(length as u32).to_ne_bytes())?;
-        writer.write_all(&(offset as u32).to_ne_bytes())?;
-        offset += length + 1;
+    let mut segment_names: Vec<&str> = Vec::new();
+    let mut segment_index: HashMap<&str, usize> = HashMap::new();
+    for (original, translated) in &sysdep_strings {
+        for name in original
+            .iter()
+            .chain(translated.iter())
+            .filter_map(|s| s.sysdep.as_deref())
+        {
+            segment_index.entry(name).or_insert_with(|| {
+                segment_names.push(name);
+                segment_names.len() - 1
+            });
+        }
     }
 
-    // O strings
-    writer.write_all(&[0u8])?;
-    for &index in catalog.map.values() {
-        write_original_repr(&mut writer, catalog.messages[index].as_ref().unwrap())?;
-        writer.write_all(&[0u8])?;
+    // Header
+    let num_strings = static_strings.len();
+    let num_sysdep_strings = sysdep_strings.len();
+    let header_size = if num_sysdep_strings == 0 { 28 } else { 48 };
+    let mut image = vec![0u8; header_size];
+    put_u32(&mut image, 0, MAGIC_NUMBER as usize);
+    put_u32(&mut image, 4, (num_sysdep_strings > 0) as usize);
+    put_u32(&mut image, 8, num_strings);
+
+    // O and T tables
+    let orig_table_offset = reserve(&mut image, 8 * num_strings);
+    let trans_table_offset = reserve(&mut image, 8 * num_strings);
+    put_u32(&mut image, 12, orig_table_offset);
+    put_u32(&mut image, 16, trans_table_offset);
+
+    // Hash table
+    // The runtime adds the expanded system-dependent strings to its copy of the hash table
+    let hash_table_size = hash_table_size(num_strings + num_sysdep_strings);
+    let hash_table_offset = reserve(&mut image, 4 * hash_table_size);
+    put_u32(&mut image, 20, hash_table_size);
+    put_u32(&mut image, 24, hash_table_offset);
+    for (i, (original, _)) in static_strings.iter().enumerate() {
+        let text = &original[0].text;
+        let key = match text.iter().position(|&c| c == 0) {
+            Some(end) => &text[..end],
+            None => text,
+        };
+        let hval = hash_string(key) as usize;
+        let mut index = hval % hash_table_size;
+        let increment = 1 + hval % (hash_table_size - 2);
+        while image[hash_table_offset + 4 * index..hash_table_offset + 4 * index + 4] != [0u8; 4] {
+            if index >= hash_table_size - increment {
+                index -= hash_table_size - increment;
+            } else {
+                index += increment;
+            }
+        }
+        put_u32(&mut image, hash_table_offset + 4 * index, i + 1);
     }
 
-    // T strings
-    writer.write_all(metadata.as_bytes())?;
-    writer.write_all(&[0u8])?;
-    for &index in catalog.map.values() {
-        write_translated_repr(&mut writer, catalog.messages[index].as_ref().unwrap())?;
-        writer.write_all(&[0u8])?;
+    // System-dependent segment and string tables
+    let mut descriptors = Vec::new();
+    if num_sysdep_strings > 0 {
+        let segment_table_offset = reserve(&mut image, 8 * segment_names.len());
+        let orig_sysdep_table_offset = reserve(&mut image, 4 * num_sysdep_strings);
+        let trans_sysdep_table_offset = reserve(&mut image, 4 * num_sysdep_strings);
+        put_u32(&mut image, 28, segment_names.len());
+        put_u32(&mut image, 32, segment_table_offset);
+        put_u32(&mut image, 36, num_sysdep_strings);
+        put_u32(&mut image, 40, orig_sysdep_table_offset);
+        put_u32(&mut image, 44, trans_sysdep_table_offset);
+        for (table_offset, pick_translated) in [
+            (orig_sysdep_table_offset, false),
+            (trans_sysdep_table_offset, true),
+        ] {
+            for (i, (original, translated)) in sysdep_strings.iter().enumerate() {
+                let segments = if pick_translated {
+                    translated
+                } else {
+                    original
+                };
+                let descriptor = reserve(&mut image, 4 + 8 * segments.len());
+                put_u32(&mut image, table_offset + 4 * i, descriptor);
+                descriptors.push((descriptor, segments));
+            }
+        }
+
+        for (i, name) in segment_names.iter().enumerate() {
+            let offset = image.len();
+            image.extend_from_slice(name.as_bytes());
+            image.push(0u8);
+            // Unlike other strings, the length of a segment name includes the terminating NUL
+            put_u32(&mut image, segment_table_offset + 8 * i, name.len() + 1);
+            put_u32(&mut image, segment_table_offset + 8 * i + 4, offset);
+        }
+    }
+
+    // O and T strings
+    for (table_offset, pick_translated) in [(orig_table_offset, false), (trans_table_offset, true)]
+    {
+        for (i, (original, translated)) in static_strings.iter().enumerate() {
+            let text = if pick_translated {
+                &translated[0].text
+            } else {
+                &original[0].text
+            };
+            let offset = image.len();
+            image.extend_from_slice(text);
+            image.push(0u8);
+            put_u32(&mut image, table_offset + 8 * i, text.len());
+            put_u32(&mut image, table_offset + 8 * i + 4, offset);
+        }
+    }
+
+    // System-dependent strings, the last segment includes the terminating NUL
+    for (descriptor, segments) in descriptors {
+        let offset = image.len();
+        put_u32(&mut image, descriptor, offset);
+        for (j, segment) in segments.iter().enumerate() {
+            image.extend_from_slice(&segment.text);
+            let sysdep_ref = match segment.sysdep.as_deref() {
+                Some(name) => segment_index[name],
+                None => {
+                    image.push(0u8);
+                    SEGMENTS_END as usize
+                }
+            };
+            let size = segment.text.len() + segment.sysdep.is_none() as usize;
+            put_u32(&mut image, descriptor + 4 + 8 * j, size);
+            put_u32(&mut image, descriptor + 8 + 8 * j, sysdep_ref);
+        }
     }
 
+    image
+}
+
+/// Saves a catalog to a binary MO file.
+pub fn write(catalog: &Catalog, path: &Path) -> Result<(), std::io::Error> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(&serialize(catalog))?;
     writer.flush()?;
     Ok(())
 }
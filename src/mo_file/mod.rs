@@ -1,22 +1,19 @@
-//! Writing to MO files.
+//! Parsing and writing to MO files.
 
+mod mo_file_batch;
+mod mo_file_compiler;
+mod mo_file_lookup;
+mod mo_file_parser;
 mod mo_file_writer;
 
+pub use mo_file_batch::{
+    compile_directory, MOBatchCompileOptions, MOBatchCompileResult, MOBatchCompileStatus,
+    MOBatchLayoutError,
+};
+pub use mo_file_compiler::{
+    compile_from_po, compile_from_po_with_option, decompile_to_po, MOCompileCheckError,
+    MOCompileOptions, MOCompileStatistics,
+};
+pub use mo_file_lookup::MOFile;
+pub use mo_file_parser::{parse, parse_from_reader, MOParseError};
 pub use mo_file_writer::write;
-
-use crate::po_file;
-use crate::po_file::POParseOptions;
-use std::error::Error;
-use std::path::Path;
-
-/// Compile a `.po` file to a `.mo` file.
-pub fn compile_from_po(input_path: &Path, output_path: &Path) -> Result<(), Box<dyn Error>> {
-    let parse_options = POParseOptions {
-        message_body_only: true,
-        translated_only: true,
-        unsafe_utf8_decode: false,
-    };
-    let catalog = po_file::parse_with_option(input_path, &parse_options)?;
-    write(&catalog, output_path)?;
-    Ok(())
-}
@@ -0,0 +1,773 @@
+//! Convert between CLDR plural categories and gettext plural forms.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use super::database::lookup;
+use super::expression::{BinaryOperator, Expression};
+use super::{CatalogPluralRules, PluralRulesError};
+use crate::metadata::LanguageTag;
+
+/// A CLDR plural category, in the order CLDR lists them.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum PluralCategory {
+    /// `zero`
+    Zero,
+    /// `one`
+    One,
+    /// `two`
+    Two,
+    /// `few`
+    Few,
+    /// `many`
+    Many,
+    /// `other`
+    Other,
+}
+
+impl PluralCategory {
+    /// All categories in the order CLDR lists them.
+    pub const ALL: [PluralCategory; 6] = [
+        PluralCategory::Zero,
+        PluralCategory::One,
+        PluralCategory::Two,
+        PluralCategory::Few,
+        PluralCategory::Many,
+        PluralCategory::Other,
+    ];
+
+    /// Name of the category in CLDR, e.g. `few`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+
+    /// Parse the CLDR name of a category.
+    pub fn parse(name: &str) -> Result<Self, PluralRulesError> {
+        PluralCategory::ALL
+            .into_iter()
+            .find(|category| category.name() == name)
+            .ok_or_else(|| PluralRulesError::from(format!("unknown plural category {}", name)))
+    }
+}
+
+impl FromStr for PluralCategory {
+    type Err = PluralRulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for PluralCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+type CldrRules = &'static [(PluralCategory, &'static str)];
+
+const ONE_N_IS_1: CldrRules = &[(PluralCategory::One, "n = 1")];
+const ONE_I_IS_1: CldrRules = &[(PluralCategory::One, "i = 1 and v = 0")];
+const ONE_I_IS_0_OR_N_IS_1: CldrRules = &[(PluralCategory::One, "i = 0 or n = 1")];
+const MANY_MILLIONS: &str = "e = 0 and i != 0 and i % 1000000 = 0 and v = 0 or e != 0..5";
+const ONE_I_IS_1_MANY: CldrRules = &[
+    (PluralCategory::One, "i = 1 and v = 0"),
+    (PluralCategory::Many, MANY_MILLIONS),
+];
+const EAST_SLAVIC: CldrRules = &[
+    (
+        PluralCategory::One,
+        "v = 0 and i % 10 = 1 and i % 100 != 11",
+    ),
+    (
+        PluralCategory::Few,
+        "v = 0 and i % 10 = 2..4 and i % 100 != 12..14",
+    ),
+    (
+        PluralCategory::Many,
+        "v = 0 and i % 10 = 0 or v = 0 and i % 10 = 5..9 or v = 0 and i % 100 = 11..14",
+    ),
+];
+const SOUTH_SLAVIC: CldrRules = &[
+    (
+        PluralCategory::One,
+        "v = 0 and i % 10 = 1 and i % 100 != 11 or f % 10 = 1 and f % 100 != 11",
+    ),
+    (
+        PluralCategory::Few,
+        "v = 0 and i % 10 = 2..4 and i % 100 != 12..14 or f % 10 = 2..4 and f % 100 != 12..14",
+    ),
+];
+const WEST_SLAVIC: CldrRules = &[
+    (PluralCategory::One, "i = 1 and v = 0"),
+    (PluralCategory::Few, "i = 2..4 and v = 0"),
+    (PluralCategory::Many, "v != 0"),
+];
+
+/// CLDR cardinal plural rules of the languages in the plural rules database, with
+/// the implicit `other` category left out.
+const CLDR_RULES: [(&str, CldrRules); 79] = [
+    ("id", &[]),
+    ("ja", &[]),
+    ("ko", &[]),
+    ("ms", &[]),
+    ("th", &[]),
+    ("vi", &[]),
+    ("zh", &[]),
+    ("km", &[]),
+    ("lo", &[]),
+    ("my", &[]),
+    ("af", ONE_N_IS_1),
+    ("bg", ONE_N_IS_1),
+    ("el", ONE_N_IS_1),
+    ("eo", ONE_N_IS_1),
+    ("eu", ONE_N_IS_1),
+    ("fo", ONE_N_IS_1),
+    ("hu", ONE_N_IS_1),
+    ("nb", ONE_N_IS_1),
+    ("nn", ONE_N_IS_1),
+    ("no", ONE_N_IS_1),
+    ("tr", ONE_N_IS_1),
+    ("az", ONE_N_IS_1),
+    ("fur", ONE_N_IS_1),
+    ("kk", ONE_N_IS_1),
+    ("ml", ONE_N_IS_1),
+    ("mn", ONE_N_IS_1),
+    ("mr", ONE_N_IS_1),
+    ("ne", ONE_N_IS_1),
+    ("sq", ONE_N_IS_1),
+    ("ta", ONE_N_IS_1),
+    ("te", ONE_N_IS_1),
+    ("de", ONE_I_IS_1),
+    ("en", ONE_I_IS_1),
+    ("et", ONE_I_IS_1),
+    ("fi", ONE_I_IS_1),
+    ("gl", ONE_I_IS_1),
+    ("nl", ONE_I_IS_1),
+    ("sv", ONE_I_IS_1),
+    ("ast", ONE_I_IS_1),
+    ("fy", ONE_I_IS_1),
+    ("ia", ONE_I_IS_1),
+    ("sw", ONE_I_IS_1),
+    ("ur", ONE_I_IS_1),
+    ("am", ONE_I_IS_0_OR_N_IS_1),
+    ("as", ONE_I_IS_0_OR_N_IS_1),
+    ("bn", ONE_I_IS_0_OR_N_IS_1),
+    ("fa", ONE_I_IS_0_OR_N_IS_1),
+    ("gu", ONE_I_IS_0_OR_N_IS_1),
+    ("hi", ONE_I_IS_0_OR_N_IS_1),
+    ("kn", ONE_I_IS_0_OR_N_IS_1),
+    ("pa", &[(PluralCategory::One, "n = 0..1")]),
+    ("ca", ONE_I_IS_1_MANY),
+    ("it", ONE_I_IS_1_MANY),
+    ("pt_PT", ONE_I_IS_1_MANY),
+    (
+        "es",
+        &[
+            (PluralCategory::One, "n = 1"),
+            (PluralCategory::Many, MANY_MILLIONS),
+        ],
+    ),
+    (
+        "pt",
+        &[
+            (PluralCategory::One, "i = 0..1"),
+            (PluralCategory::Many, MANY_MILLIONS),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            (PluralCategory::One, "i = 0,1"),
+            (PluralCategory::Many, MANY_MILLIONS),
+        ],
+    ),
+    ("da", &[(PluralCategory::One, "n = 1 or t != 0 and i = 0,1")]),
+    (
+        "he",
+        &[
+            (PluralCategory::One, "i = 1 and v = 0 or i = 0 and v != 0"),
+            (PluralCategory::Two, "i = 2 and v = 0"),
+        ],
+    ),
+    (
+        "is",
+        &[(
+            PluralCategory::One,
+            "t = 0 and i % 10 = 1 and i % 100 != 11 or t % 10 = 1 and t % 100 != 11",
+        )],
+    ),
+    (
+        "be",
+        &[
+            (PluralCategory::One, "n % 10 = 1 and n % 100 != 11"),
+            (PluralCategory::Few, "n % 10 = 2..4 and n % 100 != 12..14"),
+            (
+                PluralCategory::Many,
+                "n % 10 = 0 or n % 10 = 5..9 or n % 100 = 11..14",
+            ),
+        ],
+    ),
+    ("ru", EAST_SLAVIC),
+    ("uk", EAST_SLAVIC),
+    ("bs", SOUTH_SLAVIC),
+    ("hr", SOUTH_SLAVIC),
+    ("sr", SOUTH_SLAVIC),
+    (
+        "mk",
+        &[(
+            PluralCategory::One,
+            "v = 0 and i % 10 = 1 and i % 100 != 11 or f % 10 = 1 and f % 100 != 11",
+        )],
+    ),
+    ("cs", WEST_SLAVIC),
+    ("sk", WEST_SLAVIC),
+    (
+        "pl",
+        &[
+            (PluralCategory::One, "i = 1 and v = 0"),
+            (
+                PluralCategory::Few,
+                "v = 0 and i % 10 = 2..4 and i % 100 != 12..14",
+            ),
+            (
+                PluralCategory::Many,
+                "v = 0 and i != 1 and i % 10 = 0..1 or v = 0 and i % 10 = 5..9 or v = 0 and i % 100 = 12..14",
+            ),
+        ],
+    ),
+    (
+        "lt",
+        &[
+            (PluralCategory::One, "n % 10 = 1 and n % 100 != 11..19"),
+            (PluralCategory::Few, "n % 10 = 2..9 and n % 100 != 11..19"),
+            (PluralCategory::Many, "f != 0"),
+        ],
+    ),
+    (
+        "lv",
+        &[
+            (
+                PluralCategory::Zero,
+                "n % 10 = 0 or n % 100 = 11..19 or v = 2 and f % 100 = 11..19",
+            ),
+            (
+                PluralCategory::One,
+                "n % 10 = 1 and n % 100 != 11 or v = 2 and f % 10 = 1 and f % 100 != 11 or v != 2 and f % 10 = 1",
+            ),
+        ],
+    ),
+    (
+        "ro",
+        &[
+            (PluralCategory::One, "i = 1 and v = 0"),
+            (
+                PluralCategory::Few,
+                "v != 0 or n = 0 or n != 1 and n % 100 = 1..19",
+            ),
+        ],
+    ),
+    (
+        "ga",
+        &[
+            (PluralCategory::One, "n = 1"),
+            (PluralCategory::Two, "n = 2"),
+            (PluralCategory::Few, "n = 3..6"),
+            (PluralCategory::Many, "n = 7..10"),
+        ],
+    ),
+    (
+        "cy",
+        &[
+            (PluralCategory::Zero, "n = 0"),
+            (PluralCategory::One, "n = 1"),
+            (PluralCategory::Two, "n = 2"),
+            (PluralCategory::Few, "n = 3"),
+            (PluralCategory::Many, "n = 6"),
+        ],
+    ),
+    (
+        "mt",
+        &[
+            (PluralCategory::One, "n = 1"),
+            (PluralCategory::Two, "n = 2"),
+            (PluralCategory::Few, "n = 0 or n % 100 = 3..10"),
+            (PluralCategory::Many, "n % 100 = 11..19"),
+        ],
+    ),
+    (
+        "gd",
+        &[
+            (PluralCategory::One, "n = 1,11"),
+            (PluralCategory::Two, "n = 2,12"),
+            (PluralCategory::Few, "n = 3..10,13..19"),
+        ],
+    ),
+    (
+        "sl",
+        &[
+            (PluralCategory::One, "v = 0 and i % 100 = 1"),
+            (PluralCategory::Two, "v = 0 and i % 100 = 2"),
+            (PluralCategory::Few, "v = 0 and i % 100 = 3..4 or v != 0"),
+        ],
+    ),
+    (
+        "ar",
+        &[
+            (PluralCategory::Zero, "n = 0"),
+            (PluralCategory::One, "n = 1"),
+            (PluralCategory::Two, "n = 2"),
+            (PluralCategory::Few, "n % 100 = 3..10"),
+            (PluralCategory::Many, "n % 100 = 11..99"),
+        ],
+    ),
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    Number(u64),
+    Equal,
+    NotEqual,
+    Remainder,
+    Range,
+    Comma,
+}
+
+fn tokenize(rule: &str) -> Result<Vec<Token<'_>>, PluralRulesError> {
+    let bytes = rule.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let token = match bytes[i] {
+            c if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            c if c.is_ascii_alphanumeric() => {
+                while i < bytes.len() && bytes[i].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                let word = &rule[start..i];
+                if word.bytes().all(|c| c.is_ascii_digit()) {
+                    Token::Number(word.parse().map_err(|_| {
+                        PluralRulesError::from(format!("number too large at {}", start))
+                    })?)
+                } else {
+                    Token::Word(word)
+                }
+            }
+            b'=' => {
+                i += 1;
+                Token::Equal
+            }
+            b'!' if bytes.get(i + 1) == Some(&b'=') => {
+                i += 2;
+                Token::NotEqual
+            }
+            b'%' => {
+                i += 1;
+                Token::Remainder
+            }
+            b'.' if bytes.get(i + 1) == Some(&b'.') => {
+                i += 2;
+                Token::Range
+            }
+            b',' => {
+                i += 1;
+                Token::Comma
+            }
+            _ => {
+                return Err(PluralRulesError::from(format!(
+                    "unexpected character '{}' at {} in CLDR rule",
+                    rule[i..].chars().next().unwrap(),
+                    i
+                )))
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn and(lhs: Expression, rhs: Expression) -> Expression {
+    match (lhs, rhs) {
+        (Expression::Number(0), _) | (_, Expression::Number(0)) => Expression::Number(0),
+        (Expression::Number(_), other) | (other, Expression::Number(_)) => other,
+        (lhs, rhs) => Expression::Binary(BinaryOperator::And, Box::new(lhs), Box::new(rhs)),
+    }
+}
+
+fn or(lhs: Expression, rhs: Expression) -> Expression {
+    match (lhs, rhs) {
+        (Expression::Number(0), other) | (other, Expression::Number(0)) => other,
+        (Expression::Number(_), _) | (_, Expression::Number(_)) => Expression::Number(1),
+        (lhs, rhs) => Expression::Binary(BinaryOperator::Or, Box::new(lhs), Box::new(rhs)),
+    }
+}
+
+fn binary(operator: BinaryOperator, lhs: Expression, rhs: Expression) -> Expression {
+    let expression = Expression::Binary(operator, Box::new(lhs), Box::new(rhs));
+    match expression {
+        Expression::Binary(_, ref lhs, ref rhs)
+            if matches!(**lhs, Expression::Number(_)) && matches!(**rhs, Expression::Number(_)) =>
+        {
+            Expression::Number(expression.evaluate(0).unwrap_or(0))
+        }
+        // Operands are unsigned
+        Expression::Binary(BinaryOperator::GreaterEqual, _, ref rhs)
+            if **rhs == Expression::Number(0) =>
+        {
+            Expression::Number(1)
+        }
+        expression => expression,
+    }
+}
+
+/// Parser of CLDR plural rule conditions, evaluated for integers only.
+struct CldrParser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+}
+
+impl<'a> CldrParser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn accept_word(&mut self, word: &str) -> bool {
+        if self.peek() == Some(Token::Word(word)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, expected: &str) -> PluralRulesError {
+        PluralRulesError::from(format!(
+            "syntax error in CLDR rule at token {}: expected {}",
+            self.position + 1,
+            expected
+        ))
+    }
+
+    fn number(&mut self) -> Result<u64, PluralRulesError> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(number),
+            _ => Err(self.error("a number")),
+        }
+    }
+
+    /// `condition = and_condition ('or' and_condition)*`
+    fn condition(&mut self) -> Result<Expression, PluralRulesError> {
+        let mut result = self.and_condition()?;
+        while self.accept_word("or") {
+            result = or(result, self.and_condition()?);
+        }
+        Ok(result)
+    }
+
+    /// `and_condition = relation ('and' relation)*`
+    fn and_condition(&mut self) -> Result<Expression, PluralRulesError> {
+        let mut result = self.relation()?;
+        while self.accept_word("and") {
+            result = and(result, self.relation()?);
+        }
+        Ok(result)
+    }
+
+    /// `relation = expr ('=' | '!=' | 'is' 'not'? | 'not'? 'in' | 'not'? 'within') range_list`
+    fn relation(&mut self) -> Result<Expression, PluralRulesError> {
+        let operand = match self.next() {
+            Some(Token::Word("n" | "i")) => Expression::N,
+            // Integers have no fraction digits and no exponent
+            Some(Token::Word("v" | "w" | "f" | "t" | "c" | "e")) => Expression::Number(0),
+            _ => return Err(self.error("an operand")),
+        };
+        let operand = if self.peek() == Some(Token::Remainder) || self.accept_word("mod") {
+            if self.peek() == Some(Token::Remainder) {
+                self.position += 1;
+            }
+            binary(
+                BinaryOperator::Remainder,
+                operand,
+                Expression::Number(self.number()?),
+            )
+        } else {
+            operand
+        };
+        let negated = match self.next() {
+            Some(Token::Equal) => false,
+            Some(Token::NotEqual) => true,
+            Some(Token::Word("is")) => self.accept_word("not"),
+            Some(Token::Word("in" | "within")) => false,
+            Some(Token::Word("not")) if self.accept_word("in") || self.accept_word("within") => {
+                true
+            }
+            _ => return Err(self.error("a comparison")),
+        };
+        let mut result = Expression::Number(0);
+        loop {
+            let low = self.number()?;
+            let matches = if self.peek() == Some(Token::Range) {
+                self.position += 1;
+                let high = self.number()?;
+                and(
+                    binary(
+                        BinaryOperator::GreaterEqual,
+                        operand.clone(),
+                        Expression::Number(low),
+                    ),
+                    binary(
+                        BinaryOperator::LessEqual,
+                        operand.clone(),
+                        Expression::Number(high),
+                    ),
+                )
+            } else {
+                binary(
+                    BinaryOperator::Equal,
+                    operand.clone(),
+                    Expression::Number(low),
+                )
+            };
+            result = or(result, matches);
+            if self.peek() != Some(Token::Comma) {
+                break;
+            }
+            self.position += 1;
+        }
+        Ok(if negated { negate(result) } else { result })
+    }
+}
+
+/// Negate a condition, turning `!(n == 1)` into `n != 1` where possible.
+fn negate(condition: Expression) -> Expression {
+    match condition {
+        Expression::Number(number) => Expression::Number((number == 0) as u64),
+        Expression::Binary(operator, lhs, rhs) => {
+            let operator = match operator {
+                BinaryOperator::Equal => BinaryOperator::NotEqual,
+                BinaryOperator::NotEqual => BinaryOperator::Equal,
+                BinaryOperator::Less => BinaryOperator::GreaterEqual,
+                BinaryOperator::LessEqual => BinaryOperator::Greater,
+                BinaryOperator::Greater => BinaryOperator::LessEqual,
+                BinaryOperator::GreaterEqual => BinaryOperator::Less,
+                BinaryOperator::And => return or(negate(*lhs), negate(*rhs)),
+                BinaryOperator::Or => return and(negate(*lhs), negate(*rhs)),
+                _ => return Expression::Not(Box::new(Expression::Binary(operator, lhs, rhs))),
+            };
+            Expression::Binary(operator, lhs, rhs)
+        }
+        condition => Expression::Not(Box::new(condition)),
+    }
+}
+
+/// Parse a CLDR plural rule like `v = 0 and i % 10 = 1 @integer 1, 21, 31`, ignoring samples.
+/// Returns None if the rule has no condition, like the rule of `other`.
+fn parse_cldr_rule(rule: &str) -> Result<Option<Expression>, PluralRulesError> {
+    let condition = rule.split('@').next().unwrap();
+    let mut parser = CldrParser {
+        tokens: tokenize(condition)?,
+        position: 0,
+    };
+    if parser.tokens.is_empty() {
+        return Ok(None);
+    }
+    let result = parser.condition()?;
+    if parser.position < parser.tokens.len() {
+        return Err(parser.error("'and' or 'or'"));
+    }
+    Ok(Some(result))
+}
+
+/// Conditions of CLDR plural categories for integers, in CLDR order.
+struct CldrConditions(Vec<(PluralCategory, Expression)>);
+
+impl CldrConditions {
+    fn parse(rules: &[(PluralCategory, &str)]) -> Result<Self, PluralRulesError> {
+        let mut conditions = Vec::new();
+        for &(category, rule) in rules {
+            if category == PluralCategory::Other {
+                continue;
+            }
+            if let Some(condition) = parse_cldr_rule(rule)? {
+                conditions.push((category, condition));
+            }
+        }
+        conditions.sort_by_key(|(category, _)| *category);
+        Ok(CldrConditions(conditions))
+    }
+
+    fn category(&self, n: u64) -> PluralCategory {
+        self.0
+            .iter()
+            .find(|(_, condition)| condition.evaluate(n).unwrap_or(0) != 0)
+            .map_or(PluralCategory::Other, |(category, _)| *category)
+    }
+}
+
+/// Values of n to find the categories of a language that apply to integers.
+fn samples() -> impl Iterator<Item = u64> {
+    (0..=1000).chain((3..=18).flat_map(|k| {
+        let power = 10u64.pow(k);
+        [power, power + 1, 2 * power]
+    }))
+}
+
+impl CatalogPluralRules {
+    /// Generate plural rules from CLDR cardinal plural rules, e.g. from `plurals.xml`,
+    /// like `[(PluralCategory::One, "i = 1 and v = 0 @integer 1")]`.
+    ///
+    /// Only integers are considered, so categories that apply only to fractions, usually
+    /// `other` or `many`, do not get a plural form. Returns the rules together with the
+    /// CLDR category of each plural form.
+    pub fn from_cldr(
+        rules: &[(PluralCategory, &str)],
+    ) -> Result<(Self, Vec<PluralCategory>), PluralRulesError> {
+        let conditions = CldrConditions::parse(rules)?;
+        let mut categories: Vec<PluralCategory> =
+            samples().map(|n| conditions.category(n)).collect();
+        categories.sort();
+        categories.dedup();
+
+        let last = categories.len() - 1;
+        let mut expression = Expression::Number(last as u64);
+        for (index, category) in categories[..last].iter().enumerate().rev() {
+            let (_, condition) = conditions.0.iter().find(|(c, _)| c == category).unwrap();
+            expression = Expression::Ternary(
+                Box::new(condition.clone()),
+                Box::new(Expression::Number(index as u64)),
+                Box::new(expression),
+            );
+        }
+        let rules = CatalogPluralRules::new(categories.len(), &expression.to_string())?;
+        Ok((rules, categories))
+    }
+
+    /// CLDR plural category of each plural form of the rules, for a language like `ru`.
+    ///
+    /// Each plural form gets the category most of the integers it is used for belong to
+    /// in CLDR, or `other` if it is never used. Returns None if the language tag is invalid
+    /// or the CLDR rules of the language are not known.
+    pub fn cldr_categories(&self, language: &str) -> Option<Vec<PluralCategory>> {
+        let tag = LanguageTag::parse(language).ok()?;
+        let conditions = CldrConditions::parse(lookup(&CLDR_RULES, &tag)?).unwrap();
+        let mut counts = vec![[0usize; 6]; self.nplurals];
+        for n in 0..=1000 {
+            if let Some(count) = counts.get_mut(self.evaluate(n)) {
+                count[conditions.category(n) as usize] += 1;
+            }
+        }
+        Some(
+            counts
+                .iter()
+                .map(|count| {
+                    let (index, &max) = count
+                        .iter()
+                        .enumerate()
+                        .rev()
+                        .max_by_key(|(_, count)| **count)
+                        .unwrap();
+                    if max == 0 {
+                        PluralCategory::Other
+                    } else {
+                        PluralCategory::ALL[index]
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_cldr() {
+        let (rules, categories) = CatalogPluralRules::from_cldr(EAST_SLAVIC).unwrap();
+        assert_eq!(
+            categories,
+            vec![
+                PluralCategory::One,
+                PluralCategory::Few,
+                PluralCategory::Many
+            ]
+        );
+        assert_eq!(
+            rules.expr(),
+            "n % 10 == 1 && n % 100 != 11 ? 0 : n % 10 >= 2 && n % 10 <= 4 && (n % 100 < 12 || n % 100 > 14) ? 1 : 2"
+        );
+        assert!(rules.is_equivalent(&CatalogPluralRules::for_language("ru").unwrap()));
+
+        let (rules, categories) = CatalogPluralRules::from_cldr(&[
+            (PluralCategory::One, "i = 0,1 @integer 0, 1"),
+            (PluralCategory::Many, MANY_MILLIONS),
+            (PluralCategory::Other, " @integer 2~17, 100, 1000"),
+        ])
+        .unwrap();
+        assert_eq!(
+            categories,
+            vec![
+                PluralCategory::One,
+                PluralCategory::Many,
+                PluralCategory::Other
+            ]
+        );
+        assert_eq!(
+            rules.dump(),
+            "nplurals=3; plural=n == 0 || n == 1 ? 0 : n != 0 && n % 1000000 == 0 ? 1 : 2;"
+        );
+
+        let (rules, _) = CatalogPluralRules::from_cldr(&[]).unwrap();
+        assert_eq!(rules.dump(), "nplurals=1; plural=0;");
+        assert!(CatalogPluralRules::from_cldr(&[(PluralCategory::One, "n == 1")]).is_err());
+    }
+
+    #[test]
+    fn test_cldr_categories() {
+        for (language, rules) in CLDR_RULES {
+            let Some(gettext) = CatalogPluralRules::for_language(language) else {
+                continue;
+            };
+            let (cldr, _) = CatalogPluralRules::from_cldr(rules).unwrap();
+            assert_eq!(
+                gettext.cldr_categories(language).unwrap().len(),
+                gettext.nplurals,
+                "{}",
+                language
+            );
+            assert!(cldr.verify().is_ok(), "{}", language);
+        }
+        let french = CatalogPluralRules::for_language("fr").unwrap();
+        assert_eq!(
+            french.cldr_categories("fr"),
+            Some(vec![PluralCategory::One, PluralCategory::Other])
+        );
+        let latvian = CatalogPluralRules::for_language("lv").unwrap();
+        assert_eq!(
+            latvian.cldr_categories("lv"),
+            Some(vec![
+                PluralCategory::One,
+                PluralCategory::Other,
+                PluralCategory::Zero
+            ])
+        );
+    }
+}
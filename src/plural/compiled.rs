@@ -0,0 +1,358 @@
+//! Compile plural form expressions to hand-written fast paths or bytecode.
+
+use std::sync::OnceLock;
+
+use super::database::{
+    ABOVE_ONE, ARABIC, EAST_SLAVIC, ICELANDIC, IRISH, LATVIAN, LITHUANIAN, NOT_ONE, ONE_FORM,
+    POLISH, ROMANIAN, SLOVENIAN, WEST_SLAVIC,
+};
+use super::expression::{BinaryOperator, Expression};
+
+/// Stack depth available to bytecode programs. Deeper expressions are interpreted.
+const MAX_STACK_DEPTH: usize = 32;
+
+/// A hand-written evaluator of a plural form expression.
+type FastPath = fn(u64) -> u64;
+
+/// Hand-written evaluators of the standard plural rules in the database.
+const FAST_PATHS: [(&str, FastPath); 13] = [
+    (ONE_FORM, |_| 0),
+    (NOT_ONE, |n| (n != 1) as u64),
+    (ABOVE_ONE, |n| (n > 1) as u64),
+    (ICELANDIC, |n| (n % 10 != 1 || n % 100 == 11) as u64),
+    (EAST_SLAVIC, |n| {
+        if n % 10 == 1 && n % 100 != 11 {
+            0
+        } else if (2..=4).contains(&(n % 10)) && !(10..20).contains(&(n % 100)) {
+            1
+        } else {
+            2
+        }
+    }),
+    (WEST_SLAVIC, |n| match n {
+        1 => 0,
+        2..=4 => 1,
+        _ => 2,
+    }),
+    (POLISH, |n| {
+        if n == 1 {
+            0
+        } else if (2..=4).contains(&(n % 10)) && !(10..20).contains(&(n % 100)) {
+            1
+        } else {
+            2
+        }
+    }),
+    (LITHUANIAN, |n| {
+        if n % 10 == 1 && n % 100 != 11 {
+            0
+        } else if n % 10 >= 2 && !(10..20).contains(&(n % 100)) {
+            1
+        } else {
+            2
+        }
+    }),
+    (LATVIAN, |n| {
+        if n % 10 == 1 && n % 100 != 11 {
+            0
+        } else if n != 0 {
+            1
+        } else {
+            2
+        }
+    }),
+    (ROMANIAN, |n| {
+        if n == 1 {
+            0
+        } else if n == 0 || (1..20).contains(&(n % 100)) {
+            1
+        } else {
+            2
+        }
+    }),
+    (IRISH, |n| match n {
+        1 => 0,
+        2 => 1,
+        0..=6 => 2,
+        7..=10 => 3,
+        _ => 4,
+    }),
+    (SLOVENIAN, |n| match n % 100 {
+        1 => 0,
+        2 => 1,
+        3 | 4 => 2,
+        _ => 3,
+    }),
+    (ARABIC, |n| match n {
+        0 => 0,
+        1 => 1,
+        2 => 2,
+        _ => match n % 100 {
+            3..=10 => 3,
+            11..=99 => 4,
+            _ => 5,
+        },
+    }),
+];
+
+/// The parsed expressions of `FAST_PATHS`, parsed on first use.
+fn fast_paths() -> &'static [(Expression, FastPath)] {
+    static FAST_PATH_EXPRESSIONS: OnceLock<Vec<(Expression, FastPath)>> = OnceLock::new();
+    FAST_PATH_EXPRESSIONS.get_or_init(|| {
+        FAST_PATHS
+            .iter()
+            .map(|(rules, function)| {
+                let (_, expr) = rules.split_once("plural=").unwrap();
+                (
+                    Expression::parse(expr.trim_end_matches(';')).unwrap(),
+                    *function,
+                )
+            })
+            .collect()
+    })
+}
+
+/// An instruction of a stack machine evaluating a plural form expression.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Instruction {
+    /// Push n.
+    N,
+    /// Push a constant.
+    Const(u64),
+    /// Replace the top with its logical negation.
+    Not,
+    /// Replace the top with 1 if it is not zero.
+    ToBool,
+    /// Pop two operands and push the result. Never `&&` or `||`.
+    Binary(BinaryOperator),
+    /// Pop the top and jump if it is zero.
+    JumpIfZero(usize),
+    /// Pop the top and jump if it is not zero.
+    JumpIfNonZero(usize),
+    /// Jump unconditionally.
+    Jump(usize),
+}
+
+/// A plural form expression ready for repeated evaluation.
+#[derive(Clone, Debug)]
+pub(crate) enum CompiledExpression {
+    /// A hand-written evaluator of a standard expression.
+    Fast(FastPath),
+    /// A bytecode program with a bounded stack depth.
+    Bytecode(Vec<Instruction>),
+    /// The expression is too deep for the bytecode stack and is interpreted.
+    Interpreted,
+}
+
+struct Compiler {
+    program: Vec<Instruction>,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Compiler {
+    fn push(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::N | Instruction::Const(_) => self.depth += 1,
+            Instruction::Binary(_) | Instruction::JumpIfZero(_) | Instruction::JumpIfNonZero(_) => {
+                self.depth -= 1
+            }
+            Instruction::Not | Instruction::ToBool | Instruction::Jump(_) => {}
+        }
+        self.max_depth = self.max_depth.max(self.depth);
+        self.program.push(instruction);
+    }
+
+    /// Emit a jump to be patched with `patch` once its target is known.
+    fn push_jump(&mut self, instruction: Instruction) -> usize {
+        self.push(instruction);
+        self.program.len() - 1
+    }
+
+    /// Point a jump emitted by `push_jump` at the next instruction.
+    fn patch(&mut self, jump: usize) {
+        let target = self.program.len();
+        match &mut self.program[jump] {
+            Instruction::JumpIfZero(to)
+            | Instruction::JumpIfNonZero(to)
+            | Instruction::Jump(to) => *to = target,
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile(&mut self, expression: &Expression) {
+        match expression {
+            Expression::N => self.push(Instruction::N),
+            Expression::Number(number) => self.push(Instruction::Const(*number)),
+            Expression::Not(operand) => {
+                self.compile(operand);
+                self.push(Instruction::Not);
+            }
+            Expression::Ternary(condition, then, otherwise) => {
+                self.compile(condition);
+                let to_otherwise = self.push_jump(Instruction::JumpIfZero(0));
+                self.compile(then);
+                let to_end = self.push_jump(Instruction::Jump(0));
+                self.depth -= 1;
+                self.patch(to_otherwise);
+                self.compile(otherwise);
+                self.patch(to_end);
+            }
+            Expression::Binary(operator @ (BinaryOperator::And | BinaryOperator::Or), lhs, rhs) => {
+                // Short-circuit like C, so the right operand may divide by zero unseen.
+                let (jump, short_circuit) = match operator {
+                    BinaryOperator::And => (Instruction::JumpIfZero(0), 0),
+                    _ => (Instruction::JumpIfNonZero(0), 1),
+                };
+                self.compile(lhs);
+                let to_short_circuit = self.push_jump(jump);
+                self.compile(rhs);
+                self.push(Instruction::ToBool);
+                let to_end = self.push_jump(Instruction::Jump(0));
+                self.depth -= 1;
+                self.patch(to_short_circuit);
+                self.push(Instruction::Const(short_circuit));
+                self.patch(to_end);
+            }
+            Expression::Binary(operator, lhs, rhs) => {
+                self.compile(lhs);
+                self.compile(rhs);
+                self.push(Instruction::Binary(*operator));
+            }
+        }
+    }
+}
+
+impl CompiledExpression {
+    /// Compile an expression, using a fast path if it is a standard expression.
+    pub(crate) fn compile(expression: &Expression) -> Self {
+        if let Some((_, function)) = fast_paths().iter().find(|(fast, _)| fast == expression) {
+            return CompiledExpression::Fast(*function);
+        }
+        let mut compiler = Compiler {
+            program: Vec::new(),
+            depth: 0,
+            max_depth: 0,
+        };
+        compiler.compile(expression);
+        if compiler.max_depth > MAX_STACK_DEPTH {
+            CompiledExpression::Interpreted
+        } else {
+            CompiledExpression::Bytecode(compiler.program)
+        }
+    }
+
+    /// Evaluate the compiled form of `expression` for n. Returns None on division by zero.
+    pub(crate) fn evaluate(&self, expression: &Expression, n: u64) -> Option<u64> {
+        match self {
+            CompiledExpression::Fast(function) => Some(function(n)),
+            CompiledExpression::Bytecode(program) => run(program, n),
+            CompiledExpression::Interpreted => expression.evaluate(n),
+        }
+    }
+}
+
+fn run(program: &[Instruction], n: u64) -> Option<u64> {
+    let mut stack = [0u64; MAX_STACK_DEPTH];
+    let mut top = 0;
+    let mut pc = 0;
+    while let Some(instruction) = program.get(pc) {
+        pc += 1;
+        match *instruction {
+            Instruction::N => {
+                stack[top] = n;
+                top += 1;
+            }
+            Instruction::Const(number) => {
+                stack[top] = number;
+                top += 1;
+            }
+            Instruction::Not => stack[top - 1] = (stack[top - 1] == 0) as u64,
+            Instruction::ToBool => stack[top - 1] = (stack[top - 1] != 0) as u64,
+            Instruction::Binary(operator) => {
+                top -= 1;
+                stack[top - 1] = operator.apply(stack[top - 1], stack[top])?;
+            }
+            Instruction::JumpIfZero(target) => {
+                top -= 1;
+                if stack[top] == 0 {
+                    pc = target;
+                }
+            }
+            Instruction::JumpIfNonZero(target) => {
+                top -= 1;
+                if stack[top] != 0 {
+                    pc = target;
+                }
+            }
+            Instruction::Jump(target) => pc = target,
+        }
+    }
+    Some(stack[0])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fast_paths_match_expressions() {
+        for (expression, function) in fast_paths() {
+            for n in (0..=10000).chain([u64::MAX - 1, u64::MAX]) {
+                assert_eq!(
+                    Some(function(n)),
+                    expression.evaluate(n),
+                    "{} {}",
+                    expression,
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bytecode_matches_interpreter() {
+        let expressions = [
+            "n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 2 : 1",
+            "n==0 ? 0 : n==1 ? 1 : n%100>=3 && n%100<=10 ? 3 : 2",
+            "!(n % 3) + (n > 5) * 2 - 1",
+            "n == 0 || 10 / n > 2",
+            "n != 0 && 100 % n == 0",
+            "10 / (n - 3)",
+            "(n || 0) + (0 && n)",
+        ];
+        for expr in expressions {
+            let expression = Expression::parse(expr).unwrap();
+            let compiled = CompiledExpression::compile(&expression);
+            assert!(
+                matches!(compiled, CompiledExpression::Bytecode(_)),
+                "{}",
+                expr
+            );
+            for n in 0..=1000 {
+                assert_eq!(
+                    compiled.evaluate(&expression, n),
+                    expression.evaluate(n),
+                    "{} {}",
+                    expr,
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_compile_kinds() {
+        let expression = Expression::parse("n%10!=1 || n%100==11").unwrap();
+        assert!(matches!(
+            CompiledExpression::compile(&expression),
+            CompiledExpression::Fast(_)
+        ));
+
+        let deep = format!("{}n{}", "(1 + ".repeat(40), ")".repeat(40));
+        let expression = Expression::parse(&deep).unwrap();
+        let compiled = CompiledExpression::compile(&expression);
+        assert!(matches!(compiled, CompiledExpression::Interpreted));
+        assert_eq!(compiled.evaluate(&expression, 2), Some(42));
+    }
+}
@@ -0,0 +1,183 @@
+//! Standard plural rules of languages as chosen by `msginit`.
+
+use super::CatalogPluralRules;
+use crate::metadata::LanguageTag;
+
+pub(super) const ONE_FORM: &str = "nplurals=1; plural=0;";
+pub(super) const NOT_ONE: &str = "nplurals=2; plural=(n != 1);";
+pub(super) const ABOVE_ONE: &str = "nplurals=2; plural=(n > 1);";
+pub(super) const ICELANDIC: &str = "nplurals=2; plural=(n%10!=1 || n%100==11);";
+pub(super) const EAST_SLAVIC: &str = "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);";
+pub(super) const WEST_SLAVIC: &str = "nplurals=3; plural=(n==1) ? 0 : (n>=2 && n<=4) ? 1 : 2;";
+pub(super) const POLISH: &str =
+    "nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);";
+pub(super) const LITHUANIAN: &str =
+    "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && (n%100<10 || n%100>=20) ? 1 : 2);";
+pub(super) const LATVIAN: &str = "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n != 0 ? 1 : 2);";
+pub(super) const ROMANIAN: &str =
+    "nplurals=3; plural=n==1 ? 0 : (n==0 || (n%100 > 0 && n%100 < 20)) ? 1 : 2;";
+pub(super) const IRISH: &str = "nplurals=5; plural=n==1 ? 0 : n==2 ? 1 : n<7 ? 2 : n<11 ? 3 : 4;";
+pub(super) const SLOVENIAN: &str =
+    "nplurals=4; plural=(n%100==1 ? 0 : n%100==2 ? 1 : n%100==3 || n%100==4 ? 2 : 3);";
+const MACEDONIAN: &str = "nplurals=2; plural=(n % 10 == 1 && n % 100 != 11) ? 0 : 1;";
+const WELSH: &str = "nplurals=4; plural=(n==1) ? 0 : (n==2) ? 1 : (n != 8 && n != 11) ? 2 : 3;";
+const MALTESE: &str = "nplurals=4; plural=(n==1 ? 0 : n==0 || ( n%100>1 && n%100<11) ? 1 : (n%100>10 && n%100<20 ) ? 2 : 3);";
+const SCOTTISH_GAELIC: &str =
+    "nplurals=4; plural=(n==1 || n==11) ? 0 : (n==2 || n==12) ? 1 : (n > 2 && n < 20) ? 2 : 3;";
+pub(super) const ARABIC: &str = "nplurals=6; plural=n==0 ? 0 : n==1 ? 1 : n==2 ? 2 : n%100>=3 && n%100<=10 ? 3 : n%100>=11 ? 4 : 5;";
+
+/// Plural rules by POSIX locale name, from the plural table of GNU gettext and the
+/// rules commonly used by translation teams for other languages.
+const PLURAL_RULES: [(&str, &str); 80] = [
+    ("id", ONE_FORM),
+    ("ja", ONE_FORM),
+    ("km", ONE_FORM),
+    ("ko", ONE_FORM),
+    ("lo", ONE_FORM),
+    ("ms", ONE_FORM),
+    ("my", ONE_FORM),
+    ("th", ONE_FORM),
+    ("vi", ONE_FORM),
+    ("zh", ONE_FORM),
+    ("af", NOT_ONE),
+    ("as", NOT_ONE),
+    ("ast", NOT_ONE),
+    ("az", NOT_ONE),
+    ("bg", NOT_ONE),
+    ("bn", NOT_ONE),
+    ("ca", NOT_ONE),
+    ("da", NOT_ONE),
+    ("de", NOT_ONE),
+    ("el", NOT_ONE),
+    ("en", NOT_ONE),
+    ("eo", NOT_ONE),
+    ("es", NOT_ONE),
+    ("et", NOT_ONE),
+    ("eu", NOT_ONE),
+    ("fi", NOT_ONE),
+    ("fo", NOT_ONE),
+    ("fur", NOT_ONE),
+    ("fy", NOT_ONE),
+    ("gl", NOT_ONE),
+    ("gu", NOT_ONE),
+    ("he", NOT_ONE),
+    ("hi", NOT_ONE),
+    ("hu", NOT_ONE),
+    ("ia", NOT_ONE),
+    ("it", NOT_ONE),
+    ("kk", NOT_ONE),
+    ("kn", NOT_ONE),
+    ("ml", NOT_ONE),
+    ("mn", NOT_ONE),
+    ("mr", NOT_ONE),
+    ("nb", NOT_ONE),
+    ("ne", NOT_ONE),
+    ("nl", NOT_ONE),
+    ("nn", NOT_ONE),
+    ("no", NOT_ONE),
+    ("pa", NOT_ONE),
+    ("pt", NOT_ONE),
+    ("sq", NOT_ONE),
+    ("sv", NOT_ONE),
+    ("sw", NOT_ONE),
+    ("ta", NOT_ONE),
+    ("te", NOT_ONE),
+    ("tr", NOT_ONE),
+    ("ur", NOT_ONE),
+    ("is", ICELANDIC),
+    ("mk", MACEDONIAN),
+    ("am", ABOVE_ONE),
+    ("fa", ABOVE_ONE),
+    ("fr", ABOVE_ONE),
+    ("oc", ABOVE_ONE),
+    ("pt_BR", ABOVE_ONE),
+    ("be", EAST_SLAVIC),
+    ("bs", EAST_SLAVIC),
+    ("hr", EAST_SLAVIC),
+    ("ru", EAST_SLAVIC),
+    ("sr", EAST_SLAVIC),
+    ("uk", EAST_SLAVIC),
+    ("cs", WEST_SLAVIC),
+    ("sk", WEST_SLAVIC),
+    ("pl", POLISH),
+    ("lt", LITHUANIAN),
+    ("lv", LATVIAN),
+    ("ro", ROMANIAN),
+    ("ga", IRISH),
+    ("sl", SLOVENIAN),
+    ("cy", WELSH),
+    ("mt", MALTESE),
+    ("gd", SCOTTISH_GAELIC),
+    ("ar", ARABIC),
+];
+
+/// Find the entry of a language in a table keyed by POSIX locale name, trying the full
+/// locale name, then the language and region, then the language alone.
+pub(crate) fn lookup<T: Copy>(table: &[(&str, T)], tag: &LanguageTag) -> Option<T> {
+    let language_region = LanguageTag {
+        language: tag.language.clone(),
+        region: tag.region.clone(),
+        ..LanguageTag::default()
+    };
+    [
+        tag.to_posix(),
+        language_region.to_posix(),
+        tag.language.clone(),
+    ]
+    .iter()
+    .find_map(|name| {
+        table
+            .iter()
+            .find(|(language, _)| language == name)
+            .map(|(_, value)| *value)
+    })
+}
+
+impl CatalogPluralRules {
+    /// Standard plural rules of a language like `ru` or `pt_BR`, as chosen by `msginit`.
+    /// Returns None if the language tag is invalid or the language is not known.
+    pub fn for_language(language: &str) -> Option<Self> {
+        let tag = LanguageTag::parse(language).ok()?;
+        Some(CatalogPluralRules::parse(lookup(&PLURAL_RULES, &tag)?).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_database_is_valid() {
+        for (language, rules) in PLURAL_RULES {
+            let rules = CatalogPluralRules::parse(rules).unwrap();
+            assert_eq!(rules.verify(), Ok(()), "{}", language);
+        }
+    }
+
+    #[test]
+    fn test_for_language() {
+        let russian = CatalogPluralRules::for_language("ru_RU.UTF-8").unwrap();
+        assert_eq!(russian.nplurals, 3);
+        assert_eq!(russian.evaluate(21), 0);
+        assert_eq!(
+            CatalogPluralRules::for_language("pt-BR").unwrap().expr(),
+            "(n > 1)"
+        );
+        assert_eq!(
+            CatalogPluralRules::for_language("pt_PT").unwrap().expr(),
+            "(n != 1)"
+        );
+        assert_eq!(
+            CatalogPluralRules::for_language("sr@latin").unwrap(),
+            CatalogPluralRules::for_language("sr").unwrap()
+        );
+        assert_eq!(CatalogPluralRules::for_language("hi").unwrap().nplurals, 2);
+        let irish = CatalogPluralRules::for_language("ga").unwrap();
+        let forms: Vec<usize> = [1, 2, 3, 7, 11]
+            .iter()
+            .map(|&n| irish.evaluate(n))
+            .collect();
+        assert_eq!(forms, vec![0, 1, 2, 3, 4]);
+        assert_eq!(CatalogPluralRules::for_language("xx"), None);
+    }
+}
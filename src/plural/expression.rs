@@ -0,0 +1,434 @@
+//! Parse and evaluate plural form expressions.
+
+use std::fmt::{Display, Formatter};
+
+use super::PluralRulesError;
+
+/// Binary operators in plural form expressions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum BinaryOperator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl BinaryOperator {
+    fn symbol(self) -> &'static str {
+        match self {
+            BinaryOperator::Or => "||",
+            BinaryOperator::And => "&&",
+            BinaryOperator::Equal => "==",
+            BinaryOperator::NotEqual => "!=",
+            BinaryOperator::Less => "<",
+            BinaryOperator::LessEqual => "<=",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::GreaterEqual => ">=",
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Remainder => "%",
+        }
+    }
+
+    /// Apply the operator to evaluated operands. Returns None on division by zero.
+    pub(crate) fn apply(self, lhs: u64, rhs: u64) -> Option<u64> {
+        Some(match self {
+            BinaryOperator::Or => (lhs != 0 || rhs != 0) as u64,
+            BinaryOperator::And => (lhs != 0 && rhs != 0) as u64,
+            BinaryOperator::Equal => (lhs == rhs) as u64,
+            BinaryOperator::NotEqual => (lhs != rhs) as u64,
+            BinaryOperator::Less => (lhs < rhs) as u64,
+            BinaryOperator::LessEqual => (lhs <= rhs) as u64,
+            BinaryOperator::Greater => (lhs > rhs) as u64,
+            BinaryOperator::GreaterEqual => (lhs >= rhs) as u64,
+            BinaryOperator::Add => lhs.wrapping_add(rhs),
+            BinaryOperator::Subtract => lhs.wrapping_sub(rhs),
+            BinaryOperator::Multiply => lhs.wrapping_mul(rhs),
+            BinaryOperator::Divide => lhs.checked_div(rhs)?,
+            BinaryOperator::Remainder => lhs.checked_rem(rhs)?,
+        })
+    }
+
+    /// Binding strength of the operator, higher binds tighter.
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOperator::Or => 1,
+            BinaryOperator::And => 2,
+            BinaryOperator::Equal | BinaryOperator::NotEqual => 3,
+            BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual => 4,
+            BinaryOperator::Add | BinaryOperator::Subtract => 5,
+            BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Remainder => 6,
+        }
+    }
+}
+
+/// A parsed plural form expression, evaluated on unsigned integers like in GNU gettext.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Expression {
+    N,
+    Number(u64),
+    Not(Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Ternary(Box<Expression>, Box<Expression>, Box<Expression>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Token {
+    N,
+    Number(u64),
+    Not,
+    Operator(BinaryOperator),
+    Question,
+    Colon,
+    LeftParen,
+    RightParen,
+}
+
+fn tokenize(expr: &str) -> Result<Vec<(usize, Token)>, PluralRulesError> {
+    let bytes = expr.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let next = bytes.get(i + 1).copied();
+        let token = match (bytes[i], next) {
+            (c, _) if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            (c, _) if c.is_ascii_digit() => {
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let number = expr[start..i].parse().map_err(|_| {
+                    PluralRulesError::from(format!("number too large at {}", start))
+                })?;
+                tokens.push((start, Token::Number(number)));
+                continue;
+            }
+            (b'n', _) => Token::N,
+            (b'|', Some(b'|')) => Token::Operator(BinaryOperator::Or),
+            (b'&', Some(b'&')) => Token::Operator(BinaryOperator::And),
+            (b'=', Some(b'=')) => Token::Operator(BinaryOperator::Equal),
+            (b'!', Some(b'=')) => Token::Operator(BinaryOperator::NotEqual),
+            (b'<', Some(b'=')) => Token::Operator(BinaryOperator::LessEqual),
+            (b'>', Some(b'=')) => Token::Operator(BinaryOperator::GreaterEqual),
+            (b'!', _) => Token::Not,
+            (b'<', _) => Token::Operator(BinaryOperator::Less),
+            (b'>', _) => Token::Operator(BinaryOperator::Greater),
+            (b'+', _) => Token::Operator(BinaryOperator::Add),
+            (b'-', _) => Token::Operator(BinaryOperator::Subtract),
+            (b'*', _) => Token::Operator(BinaryOperator::Multiply),
+            (b'/', _) => Token::Operator(BinaryOperator::Divide),
+            (b'%', _) => Token::Operator(BinaryOperator::Remainder),
+            (b'?', _) => Token::Question,
+            (b':', _) => Token::Colon,
+            (b'(', _) => Token::LeftParen,
+            (b')', _) => Token::RightParen,
+            _ => {
+                return Err(PluralRulesError::from(format!(
+                    "unexpected character '{}' at {}",
+                    expr[i..].chars().next().unwrap(),
+                    i
+                )))
+            }
+        };
+        i += match token {
+            Token::Operator(
+                BinaryOperator::Or
+                | BinaryOperator::And
+                | BinaryOperator::Equal
+                | BinaryOperator::NotEqual
+                | BinaryOperator::LessEqual
+                | BinaryOperator::GreaterEqual,
+            ) => 2,
+            _ => 1,
+        };
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+/// Maximum nesting of subexpressions, which bounds the recursion of parsing, compiling
+/// and evaluating an expression from an untrusted header.
+const MAX_DEPTH: usize = 100;
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    length: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.position).map(|(_, token)| *token)
+    }
+
+    fn error(&self, expected: &str) -> PluralRulesError {
+        match self.tokens.get(self.position) {
+            Some((offset, _)) => {
+                PluralRulesError::from(format!("syntax error at {}: expected {}", offset, expected))
+            }
+            None => PluralRulesError::from(format!(
+                "syntax error at {}: expected {}",
+                self.length, expected
+            )),
+        }
+    }
+
+    /// Go one level deeper into the expression. Callers restore `depth` when done.
+    fn enter(&mut self) -> Result<(), PluralRulesError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(PluralRulesError::from(format!(
+                "expression nested deeper than {} levels",
+                MAX_DEPTH
+            )));
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, token: Token, expected: &str) -> Result<(), PluralRulesError> {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    /// `condition ? expression : expression`, right associative.
+    fn parse_expression(&mut self) -> Result<Expression, PluralRulesError> {
+        self.enter()?;
+        let condition = self.parse_binary(1)?;
+        let expression = if self.peek() == Some(Token::Question) {
+            self.position += 1;
+            let then = self.parse_expression()?;
+            self.expect(Token::Colon, "':'")?;
+            let otherwise = self.parse_expression()?;
+            Expression::Ternary(Box::new(condition), Box::new(then), Box::new(otherwise))
+        } else {
+            condition
+        };
+        self.depth -= 1;
+        Ok(expression)
+    }
+
+    /// Binary operators binding at least as tight as `min_precedence`, left associative.
+    /// Each operator nests the operands before it one level deeper.
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expression, PluralRulesError> {
+        let depth = self.depth;
+        let mut lhs = self.parse_unary()?;
+        while let Some(Token::Operator(operator)) = self.peek() {
+            if operator.precedence() < min_precedence {
+                break;
+            }
+            self.enter()?;
+            self.position += 1;
+            let rhs = self.parse_binary(operator.precedence() + 1)?;
+            lhs = Expression::Binary(operator, Box::new(lhs), Box::new(rhs));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, PluralRulesError> {
+        let token = self.peek().ok_or_else(|| self.error("an operand"))?;
+        self.position += 1;
+        match token {
+            Token::N => Ok(Expression::N),
+            Token::Number(number) => Ok(Expression::Number(number)),
+            Token::Not => {
+                self.enter()?;
+                let operand = self.parse_unary()?;
+                self.depth -= 1;
+                Ok(Expression::Not(Box::new(operand)))
+            }
+            Token::LeftParen => {
+                let expression = self.parse_expression()?;
+                self.expect(Token::RightParen, "')'")?;
+                Ok(expression)
+            }
+            _ => {
+                self.position -= 1;
+                Err(self.error("an operand"))
+            }
+        }
+    }
+}
+
+impl Expression {
+    /// Parse a plural form expression like `n%10==1 && n%100!=11 ? 0 : 1`.
+    pub(crate) fn parse(expr: &str) -> Result<Self, PluralRulesError> {
+        let mut parser = Parser {
+            tokens: tokenize(expr)?,
+            position: 0,
+            length: expr.len(),
+            depth: 0,
+        };
+        let expression = parser.parse_expression()?;
+        if parser.position < parser.tokens.len() {
+            return Err(parser.error("an operator"));
+        }
+        Ok(expression)
+    }
+
+    /// Evaluate the expression for n. Returns None on division by zero.
+    pub(crate) fn evaluate(&self, n: u64) -> Option<u64> {
+        Some(match self {
+            Expression::N => n,
+            Expression::Number(number) => *number,
+            Expression::Not(operand) => (operand.evaluate(n)? == 0) as u64,
+            Expression::Ternary(condition, then, otherwise) => {
+                if condition.evaluate(n)? != 0 {
+                    then.evaluate(n)?
+                } else {
+                    otherwise.evaluate(n)?
+                }
+            }
+            Expression::Binary(BinaryOperator::Or, lhs, rhs) => {
+                (lhs.evaluate(n)? != 0 || rhs.evaluate(n)? != 0) as u64
+            }
+            Expression::Binary(BinaryOperator::And, lhs, rhs) => {
+                (lhs.evaluate(n)? != 0 && rhs.evaluate(n)? != 0) as u64
+            }
+            Expression::Binary(operator, lhs, rhs) => {
+                operator.apply(lhs.evaluate(n)?, rhs.evaluate(n)?)?
+            }
+        })
+    }
+}
+
+impl Expression {
+    /// Binding strength of the outermost operator, higher binds tighter.
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Ternary(..) => 0,
+            Expression::Binary(operator, ..) => operator.precedence(),
+            _ => 7,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut Formatter<'_>, min_precedence: u8) -> std::fmt::Result {
+        if self.precedence() < min_precedence {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::N => write!(f, "n"),
+            Expression::Number(number) => write!(f, "{}", number),
+            Expression::Not(operand) => {
+                write!(f, "!")?;
+                operand.fmt_operand(f, 7)
+            }
+            Expression::Binary(operator, lhs, rhs) => {
+                lhs.fmt_operand(f, operator.precedence())?;
+                write!(f, " {} ", operator.symbol())?;
+                rhs.fmt_operand(f, operator.precedence() + 1)
+            }
+            Expression::Ternary(condition, then, otherwise) => {
+                condition.fmt_operand(f, 1)?;
+                write!(f, " ? ")?;
+                then.fmt_operand(f, 1)?;
+                write!(f, " : {}", otherwise)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        let russian = Expression::parse(
+            "(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2)",
+        )
+        .unwrap();
+        let forms: Vec<u64> = [0, 1, 2, 5, 11, 21, 22, 25, 111, 1001]
+            .iter()
+            .map(|&n| russian.evaluate(n).unwrap())
+            .collect();
+        assert_eq!(forms, vec![2, 0, 1, 2, 2, 0, 1, 2, 2, 0]);
+
+        let arithmetic = Expression::parse("!(n - 1) + 2 * 3 % 4 / n").unwrap();
+        assert_eq!(arithmetic.evaluate(1), Some(3));
+        assert_eq!(arithmetic.evaluate(0), None);
+    }
+
+    #[test]
+    fn test_display() {
+        for expr in [
+            "n % 10 == 1 && n % 100 != 11 ? 0 : n != 0 ? 1 : 2",
+            "(n == 1 || n == 2) && !(n > 3)",
+            "n - (n - 1) * 2",
+            "(n ? 1 : 0) ? 1 : 2",
+        ] {
+            assert_eq!(Expression::parse(expr).unwrap().to_string(), expr);
+        }
+    }
+
+    #[test]
+    fn test_parse_nested() {
+        let expr = format!("{}n{}", "(".repeat(30), ")".repeat(30));
+        assert_eq!(Expression::parse(&expr).unwrap().evaluate(5), Some(5));
+        let expr = format!("{}n", "n + ".repeat(90));
+        assert_eq!(Expression::parse(&expr).unwrap().evaluate(1), Some(91));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        for (expr, message) in [
+            ("n >", "syntax error at 3: expected an operand"),
+            ("n ? 1", "syntax error at 5: expected ':'"),
+            ("(n != 1", "syntax error at 7: expected ')'"),
+            ("n != 1)", "syntax error at 6: expected an operator"),
+            ("n = 1", "unexpected character '=' at 2"),
+            (
+                &format!("{}n{}", "(".repeat(200000), ")".repeat(200000)),
+                "expression nested deeper than 100 levels",
+            ),
+            (
+                &format!("{}n", "n + ".repeat(200000)),
+                "expression nested deeper than 100 levels",
+            ),
+            (
+                &format!("{}n", "!".repeat(200000)),
+                "expression nested deeper than 100 levels",
+            ),
+            (
+                &format!("{}0", "n ? 1 : ".repeat(200000)),
+                "expression nested deeper than 100 levels",
+            ),
+        ] {
+            assert_eq!(
+                Expression::parse(expr).unwrap_err(),
+                PluralRulesError::from(message),
+                "{}",
+                expr
+            );
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! Defines `CatalogPluralRules` struct and evaluation of plural form expressions.
+
+mod cldr;
+mod compiled;
+mod database;
+mod expression;
+
+use std::ops::RangeInclusive;
+
+use compiled::CompiledExpression;
+use expression::Expression;
+
+pub use cldr::PluralCategory;
+
+/// The plural form resolution rule of the target language.
+#[derive(Clone, Debug)]
+pub struct CatalogPluralRules {
+    /// Total number of plural forms, including singular form.
+    pub nplurals: usize,
+    /// The plural form resolution expression in the function of n.
+    expr: String,
+    /// `expr` parsed.
+    expression: Expression,
+    /// `expression` compiled for fast evaluation.
+    compiled: CompiledExpression,
+}
+
+impl Default for CatalogPluralRules {
+    fn default() -> Self {
+        let expression = Expression::Number(0);
+        Self {
+            nplurals: 1,
+            expr: String::from("0"),
+            compiled: CompiledExpression::compile(&expression),
+            expression,
+        }
+    }
+}
+
+impl PartialEq for CatalogPluralRules {
+    fn eq(&self, other: &Self) -> bool {
+        self.nplurals == other.nplurals && self.expr == other.expr
+    }
+}
+
+impl Eq for CatalogPluralRules {}
+
+/// Error type when parsing an invalid plural rules.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PluralRulesError {
+    message: String,
+}
+
+impl From<&str> for PluralRulesError {
+    fn from(s: &str) -> Self {
+        Self {
+            message: s.to_string(),
+        }
+    }
+}
+
+impl From<String> for PluralRulesError {
+    fn from(s: String) -> Self {
+        Self { message: s }
+    }
+}
+
+impl From<std::num::ParseIntError> for PluralRulesError {
+    fn from(value: std::num::ParseIntError) -> Self {
+        Self {
+            message: format!("cannot parse nplurals: {}", value),
+        }
+    }
+}
+
+impl std::fmt::Display for PluralRulesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid plural rules: {}", self.message)
+    }
+}
+
+impl std::error::Error for PluralRulesError {}
+
+/// Error type when plural rules do not yield a valid plural form index for some n.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PluralRulesVerifyError {
+    /// The expression yields an index not below `nplurals` for n.
+    OutOfRange {
+        /// The value of n.
+        n: u64,
+        /// The index yielded by the expression.
+        index: u64,
+    },
+    /// The expression divides by zero for n.
+    DivisionByZero {
+        /// The value of n.
+        n: u64,
+    },
+}
+
+impl std::fmt::Display for PluralRulesVerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PluralRulesVerifyError::OutOfRange { n, index } => write!(
+                f,
+                "plural expression yields {} for n = {}, which is not below nplurals",
+                index, n
+            ),
+            PluralRulesVerifyError::DivisionByZero { n } => {
+                write!(f, "plural expression divides by zero for n = {}", n)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PluralRulesVerifyError {}
+
+impl CatalogPluralRules {
+    /// Create plural rules from the number of plural forms and the expression.
+    /// Returns an error if `nplurals` is zero or the expression cannot be parsed.
+    pub fn new(nplurals: usize, expr: &str) -> Result<Self, PluralRulesError> {
+        if nplurals == 0 {
+            return Err(PluralRulesError::from("nplurals equals to zero"));
+        }
+        let expression = Expression::parse(expr)?;
+        Ok(CatalogPluralRules {
+            nplurals,
+            expr: String::from(expr),
+            compiled: CompiledExpression::compile(&expression),
+            expression,
+        })
+    }
+
+    /// The plural form resolution expression in the function of n.
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+
+    /// Replace the plural form resolution expression and compile it.
+    /// Returns an error and keeps the current expression if it cannot be parsed.
+    pub fn set_expr(&mut self, expr: &str) -> Result<(), PluralRulesError> {
+        let expression = Expression::parse(expr)?;
+        self.expr = String::from(expr);
+        self.compiled = CompiledExpression::compile(&expression);
+        self.expression = expression;
+        Ok(())
+    }
+
+    /// Parse a plural resolution rules from string form stored in PO file.
+    pub fn parse(rules: &str) -> Result<Self, PluralRulesError> {
+        let mut nplurals: Option<usize> = None;
+        let mut expr: Option<&str> = None;
+        for rule in rules.split(';') {
+            let rule = rule.trim();
+            if rule.is_empty() {
+                continue;
+            }
+            if let Some((key, value)) = rule.split_once('=') {
+                match key {
+                    "nplurals" => {
+                        nplurals = Some(value.parse()?);
+                    }
+                    "plural" => {
+                        expr = Some(value);
+                    }
+                    unrecognized => {
+                        return Err(PluralRulesError::from(format!(
+                            "unrecognized entry {}",
+                            unrecognized
+                        )));
+                    }
+                }
+            } else {
+                return Err(PluralRulesError::from(format!("cannot parse {}", rule)));
+            }
+        }
+        if let (Some(nplurals), Some(expr)) = (nplurals, expr) {
+            if expr.is_empty() {
+                Err(PluralRulesError::from("plural rule expression is empty"))
+            } else {
+                CatalogPluralRules::new(nplurals, expr)
+            }
+        } else if nplurals.is_none() {
+            Err(PluralRulesError::from("nplurals does not exist"))
+        } else if expr.is_none() {
+            Err(PluralRulesError::from(
+                "plural rule expression does not exist",
+            ))
+        } else {
+            std::unreachable!();
+        }
+    }
+
+    /// Index of the plural form to use for n, evaluated with the compiled expression.
+    ///
+    /// Like the GNU gettext runtime, an index that is not below `nplurals`, or a division
+    /// by zero, gives 0.
+    pub fn evaluate(&self, n: u64) -> usize {
+        self.index_in_range(self.compiled.evaluate(&self.expression, n))
+    }
+
+    /// Index of the plural form to use for n like `evaluate`, but walking the parsed
+    /// expression tree instead of the compiled form. Meant for cross-checks and benchmarks.
+    pub fn evaluate_interpreted(&self, n: u64) -> usize {
+        self.index_in_range(self.expression.evaluate(n))
+    }
+
+    fn index_in_range(&self, index: Option<u64>) -> usize {
+        match index {
+            Some(index) if index < self.nplurals as u64 => index as usize,
+            _ => 0,
+        }
+    }
+
+    /// Check that the expression yields an index below `nplurals` for every n in a range.
+    pub fn verify_range(&self, range: RangeInclusive<u64>) -> Result<(), PluralRulesVerifyError> {
+        for n in range {
+            match self.expression.evaluate(n) {
+                Some(index) if index < self.nplurals as u64 => {}
+                Some(index) => return Err(PluralRulesVerifyError::OutOfRange { n, index }),
+                None => return Err(PluralRulesVerifyError::DivisionByZero { n }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the expression yields an index below `nplurals` for every n
+    /// from 0 to 1000, like `msgfmt --check` does.
+    pub fn verify(&self) -> Result<(), PluralRulesVerifyError> {
+        self.verify_range(0..=1000)
+    }
+
+    /// Are the rules equivalent to other rules? The expressions are compared by their
+    /// results for n from 0 to 1000.
+    pub fn is_equivalent(&self, other: &CatalogPluralRules) -> bool {
+        self.nplurals == other.nplurals
+            && (0..=1000).all(|n| self.expression.evaluate(n) == other.expression.evaluate(n))
+    }
+
+    /// Sample values of n for each plural form index, like the hints shown to translators
+    /// by Poedit. Lists at most `count` values from 0 to 1000 per index, in ascending order;
+    /// the list of an index no n in that range maps to is empty.
+    pub fn examples(&self, count: usize) -> Vec<Vec<u64>> {
+        let mut examples = vec![Vec::new(); self.nplurals];
+        for n in 0..=1000 {
+            match examples.get_mut(self.evaluate(n)) {
+                Some(list) if list.len() < count => list.push(n),
+                _ => {}
+            }
+        }
+        examples
+    }
+
+    /// Dump the plural resolution rules to string form to write to a PO file.
+    pub fn dump(&self) -> String {
+        format!("nplurals={}; plural={};", self.nplurals, self.expr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plural_rules_evaluate() {
+        let mut rules = CatalogPluralRules::parse("nplurals=2; plural=(n > 1);").unwrap();
+        assert_eq!(rules.evaluate(0), 0);
+        assert_eq!(rules.evaluate(1), 0);
+        assert_eq!(rules.evaluate(2), 1);
+
+        rules.set_expr("n + 1").unwrap();
+        assert_eq!(rules.expr(), "n + 1");
+        assert_eq!(rules.evaluate(0), 1);
+        assert_eq!(rules.evaluate(1), 0);
+        assert!(rules.set_expr("n +").is_err());
+        assert_eq!(rules.evaluate(0), 1);
+
+        assert!(CatalogPluralRules::parse("nplurals=2; plural=n !=;").is_err());
+    }
+
+    #[test]
+    fn test_plural_rules_evaluate_compiled() {
+        let rules = [
+            "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);",
+            "nplurals=2; plural=n%10==1 ? 0 : 5;",
+            "nplurals=2; plural=1/(n-2);",
+        ];
+        for rules in rules {
+            let rules = CatalogPluralRules::parse(rules).unwrap();
+            for n in 0..=1000 {
+                assert_eq!(rules.evaluate(n), rules.evaluate_interpreted(n), "{}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_plural_rules_examples() {
+        let rules = CatalogPluralRules::for_language("ru").unwrap();
+        assert_eq!(
+            rules.examples(4),
+            vec![vec![1, 21, 31, 41], vec![2, 3, 4, 22], vec![0, 5, 6, 7]]
+        );
+        let mut rules = CatalogPluralRules::new(3, "n != 1").unwrap();
+        assert_eq!(rules.examples(2), vec![vec![1], vec![0, 2], vec![]]);
+        rules.nplurals = 0;
+        assert!(rules.examples(2).is_empty());
+    }
+
+    #[test]
+    fn test_plural_rules_new() {
+        assert!(CatalogPluralRules::new(0, "0").is_err());
+        assert!(CatalogPluralRules::parse("nplurals=0; plural=0;").is_err());
+        assert!(CatalogPluralRules::new(1, "0").is_ok());
+        let deep = format!("{}n{}", "(".repeat(200000), ")".repeat(200000));
+        assert!(CatalogPluralRules::parse(&format!("nplurals=2; plural={}", deep)).is_err());
+    }
+
+    #[test]
+    fn test_plural_rules_verify() {
+        let rules = CatalogPluralRules::new(2, "n%10").unwrap();
+        assert_eq!(
+            rules.verify(),
+            Err(PluralRulesVerifyError::OutOfRange { n: 2, index: 2 })
+        );
+        assert_eq!(rules.verify_range(10..=11), Ok(()));
+
+        let rules = CatalogPluralRules::parse("nplurals=2; plural=10/(n-5)>0;").unwrap();
+        assert_eq!(
+            rules.verify(),
+            Err(PluralRulesVerifyError::DivisionByZero { n: 5 })
+        );
+    }
+}
@@ -8,4 +8,8 @@ pub use po_file_parser::{
     parse, parse_from_reader, parse_from_reader_with_option, parse_with_option,
 };
 pub use po_file_parser::{POParseError, POParseOptions};
-pub use po_file_writer::{write, write_to_file, write_sort_by, write_to_file_sort_by};
+pub use po_file_writer::POWriteOptions;
+pub use po_file_writer::{
+    write, write_sort_by, write_to_file, write_to_file_sort_by, write_to_file_with_option,
+    write_with_option,
+};
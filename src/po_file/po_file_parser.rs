@@ -99,6 +99,7 @@ enum POMessageField {
 
 #[derive(Default, Debug)]
 struct POMessage {
+    translator_comments: Vec<String>,
     comments: String,
     source: String,
     flags: String,
@@ -188,6 +189,8 @@ impl POParserState {
             if po_message.msgid.is_empty() && !po_message.msgstr.is_empty() {
                 let unescaped = unescape(&po_message.msgstr)?;
                 self.catalog.metadata = CatalogMetadata::parse(&unescaped)?;
+                self.catalog.metadata.comments = po_message.translator_comments.join("\n");
+                self.catalog.metadata.flags = MessageFlags::from_str(&po_message.flags).unwrap();
                 self.metadata_parsed = true;
             } else {
                 return Err(POParseError::new(
@@ -255,7 +258,16 @@ impl POParserState {
                 self.dirty = false;
             }
         } else if line.starts_with('#') {
-            if !self.options.message_body_only {
+            if self.options.message_body_only {
+                // Skip comments and flags
+            } else if is_plural_example(line) {
+                // Generated by `POWriteOptions::plural_examples` and regenerated on write
+            } else if !self.metadata_parsed && (line == "#" || line.starts_with("# ")) {
+                // Translator comments are only kept for the header entry. They do not start
+                // the entry, so a blank line may separate them from `msgid ""`.
+                let comment = line.get(2..).unwrap_or("").to_string();
+                self.current_message.translator_comments.push(comment);
+            } else {
                 for (prefix, field) in &HEADER_FIELDS {
                     if line.starts_with(*prefix) {
                         self.current_field = *field;
@@ -283,6 +295,13 @@ impl POParserState {
     }
 }
 
+/// Is the line a `# msgstr[i]: ...` comment written by `POWriteOptions::plural_examples`?
+fn is_plural_example(line: &str) -> bool {
+    line.strip_prefix("# msgstr[")
+        .and_then(|rest| rest.split_once("]: "))
+        .is_some_and(|(index, _)| !index.is_empty() && index.bytes().all(|c| c.is_ascii_digit()))
+}
+
 /// Parse a PO file with custom parse options and returns a catalog on success.
 pub fn parse_from_reader_with_option<R: Read>(
     read: R,
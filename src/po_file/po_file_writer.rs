@@ -3,11 +3,36 @@
 use super::escape::escape;
 use crate::catalog::Catalog;
 use crate::message::MessageView;
+use crate::metadata::CatalogCharset;
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{BufWriter, ErrorKind, Write};
 use std::path::Path;
 
+/// PO file write options.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct POWriteOptions {
+    /// If true, set `PO-Revision-Date` to the current time in the written file.
+    /// The catalog itself is left unchanged.
+    pub update_revision_date: bool,
+    /// If true, precede each plural message with `# msgstr[i]: ...` comments listing
+    /// sample values of n that select each plural form. The parser drops these comments.
+    pub plural_examples: bool,
+    /// If true, also fail with `ErrorKind::InvalidData` if `Content-Type` declares a charset
+    /// other than UTF-8 or ASCII, even if every string is ASCII.
+    pub check_charset: bool,
+}
+
+impl POWriteOptions {
+    /// Creates a default POWriteOptions
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+type MessageComparator = Box<dyn FnMut(&&dyn MessageView, &&dyn MessageView) -> Ordering>;
+
 fn display_width(content: &str) -> usize {
     content.chars().count()
 }
@@ -101,13 +126,80 @@ fn write_field<W: Write>(
     Ok(())
 }
 
+/// Make sure every string can be written in the charset declared in `Content-Type`.
+/// PO files are always written in UTF-8, which agrees with other charsets on ASCII only.
+fn check_charset(catalog: &Catalog, require_supported: bool) -> Result<(), std::io::Error> {
+    let invalid = |message: String| std::io::Error::new(ErrorKind::InvalidData, message);
+    let metadata = &catalog.metadata;
+    let charset = match metadata.charset() {
+        Ok(CatalogCharset::Utf8) => return Ok(()),
+        Ok(charset) => charset,
+        Err(e) if require_supported => return Err(invalid(e.to_string())),
+        Err(_) => CatalogCharset::Ascii,
+    };
+    let name = metadata.charset_name().unwrap_or(charset.name());
+    if !charset.can_encode(&metadata.export_for_po()) || !charset.can_encode(&metadata.comments) {
+        return Err(invalid(format!("metadata cannot be encoded in {}", name)));
+    }
+    for message in catalog.messages() {
+        let encodable = [
+            message.comments(),
+            message.source(),
+            message.msgctxt().unwrap_or(""),
+            message.msgid(),
+            message.msgid_plural().unwrap_or(""),
+            message.msgstr().unwrap_or(""),
+        ]
+        .into_iter()
+        .chain(
+            message
+                .msgstr_plural()
+                .into_iter()
+                .flatten()
+                .map(String::as_str),
+        )
+        .all(|text| charset.can_encode(text));
+        if !encodable {
+            return Err(invalid(format!(
+                "message \"{}\" cannot be encoded in {}",
+                message.msgid(),
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
 fn write_internal<W: Write>(
     catalog: &Catalog,
     writer: &mut BufWriter<W>,
-    comparator: Option<Box<dyn FnMut(&&dyn MessageView, &&dyn MessageView) -> Ordering>>,
+    comparator: Option<MessageComparator>,
+    options: &POWriteOptions,
 ) -> Result<(), std::io::Error> {
-    writer.write_all(b"\nmsgid \"\"\n")?;
-    write_field(writer, "msgstr", catalog.metadata.export_for_po().as_str())?;
+    check_charset(catalog, options.check_charset)?;
+    let mut metadata = Cow::Borrowed(&catalog.metadata);
+    if options.update_revision_date {
+        metadata.to_mut().touch_revision_date();
+    }
+    writer.write_all(b"\n")?;
+    if !metadata.comments.is_empty() {
+        for line in metadata.comments.split('\n') {
+            if line.is_empty() {
+                writer.write_all(b"#\n")?;
+            } else {
+                writer.write_all(b"# ")?;
+                writer.write_all(line.as_bytes())?;
+                writer.write_all(b"\n")?;
+            }
+        }
+    }
+    if !metadata.flags.is_empty() {
+        writer.write_all(b"#, ")?;
+        writer.write_all(metadata.flags.to_string().as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    writer.write_all(b"msgid \"\"\n")?;
+    write_field(writer, "msgstr", metadata.export_for_po().as_str())?;
     writer.write_all(b"\n")?;
 
     let messages = if let Some(comparator) = comparator {
@@ -118,7 +210,20 @@ fn write_internal<W: Write>(
         catalog.messages().collect::<Vec<&dyn MessageView>>()
     };
 
+    let plural_examples = if options.plural_examples {
+        metadata.plural_rules.examples(6)
+    } else {
+        Vec::new()
+    };
     for message in messages {
+        if message.is_plural() {
+            for (i, examples) in plural_examples.iter().enumerate() {
+                let examples: Vec<String> = examples.iter().map(u64::to_string).collect();
+                let line = format!("# msgstr[{}]: {}", i, examples.join(", "));
+                writer.write_all(line.trim_end().as_bytes())?;
+                writer.write_all(b"\n")?;
+            }
+        }
         if !message.comments().is_empty() {
             for line in message.comments().split('\n') {
                 writer.write_all(b"#. ")?;
@@ -158,35 +263,66 @@ fn write_internal<W: Write>(
     Ok(())
 }
 
-/// Writes a catalog in PO format.
+/// Writes a catalog in PO format. The output is UTF-8, so it fails with
+/// `ErrorKind::InvalidData` if `Content-Type` declares another charset and some string
+/// cannot be encoded in it.
 pub fn write<W: Write>(catalog: &Catalog, writer: &mut BufWriter<W>) -> Result<(), std::io::Error> {
-    write_internal(catalog, writer, None)
+    write_internal(catalog, writer, None, &POWriteOptions::default())
 }
 
 /// Writes a catalog to a PO file on disk.
 pub fn write_to_file(catalog: &Catalog, path: &Path) -> Result<(), std::io::Error> {
     let file = File::create(path)?;
     let mut writer = BufWriter::new(file);
-    write_internal(catalog, &mut writer, None)
+    write_internal(catalog, &mut writer, None, &POWriteOptions::default())
+}
+
+/// Writes a catalog in PO format with custom write options.
+pub fn write_with_option<W: Write>(
+    catalog: &Catalog,
+    writer: &mut BufWriter<W>,
+    options: &POWriteOptions,
+) -> Result<(), std::io::Error> {
+    write_internal(catalog, writer, None, options)
+}
+
+/// Writes a catalog to a PO file on disk with custom write options.
+pub fn write_to_file_with_option(
+    catalog: &Catalog,
+    path: &Path,
+    options: &POWriteOptions,
+) -> Result<(), std::io::Error> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+    write_internal(catalog, &mut writer, None, options)
 }
 
 /// Writes a catalog in PO format with a sorting algorithm.
 pub fn write_sort_by<W: Write>(
     catalog: &Catalog,
     writer: &mut BufWriter<W>,
-    comparator: Box<dyn FnMut(&&dyn MessageView, &&dyn MessageView) -> Ordering>,
+    comparator: MessageComparator,
 ) -> Result<(), std::io::Error> {
-    write_internal(catalog, writer, Some(comparator))
+    write_internal(
+        catalog,
+        writer,
+        Some(comparator),
+        &POWriteOptions::default(),
+    )
 }
 
-
 /// Writes a catalog to a PO file on disk with a sorting algorithm.
 pub fn write_to_file_sort_by(
     catalog: &Catalog,
     path: &Path,
-    comparator: Box<dyn FnMut(&&dyn MessageView, &&dyn MessageView) -> Ordering>,
+    comparator: MessageComparator,
 ) -> Result<(), std::io::Error> {
     let file = File::create(path)?;
     let mut writer = BufWriter::new(file);
-    write_internal(catalog, &mut writer, Some(comparator))
+    write_internal(
+        catalog,
+        &mut writer,
+        Some(comparator),
+        &POWriteOptions::default(),
+    )
 }
@@ -0,0 +1,299 @@
+//! Defines `TextDomains` registry mimicking `bindtextdomain`, `textdomain` and `dgettext`.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::{fallback_locales, FallbackChain, Translator};
+use crate::catalog::Catalog;
+use crate::mo_file::{self, MOParseError};
+use crate::po_file::{self, POParseError, POParseOptions};
+
+/// Error when a catalog file exists but cannot be loaded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextDomainError {
+    message: String,
+}
+
+impl From<std::io::Error> for TextDomainError {
+    fn from(value: std::io::Error) -> Self {
+        Self {
+            message: value.to_string(),
+        }
+    }
+}
+
+impl From<MOParseError> for TextDomainError {
+    fn from(value: MOParseError) -> Self {
+        Self {
+            message: value.to_string(),
+        }
+    }
+}
+
+impl From<POParseError> for TextDomainError {
+    fn from(value: POParseError) -> Self {
+        Self {
+            message: value.to_string(),
+        }
+    }
+}
+
+impl Display for TextDomainError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for TextDomainError {}
+
+/// Translators loaded so far by domain and locale, None if there is no catalog.
+/// Load failures are kept so that a broken catalog is not read again.
+type TranslatorCache =
+    HashMap<String, HashMap<String, Result<Option<Arc<Translator>>, TextDomainError>>>;
+
+/// A registry of gettext text domains, each bound to a directory of catalogs laid out as
+/// `<dir>/<locale>/LC_MESSAGES/<domain>.mo`, or `.po` if there is no MO file.
+///
+/// Catalogs are loaded on first lookup and cached per domain and locale, along with the
+/// fallback chain of each domain. Messages are looked up in the catalogs of the locales
+/// and their fallbacks, e.g. `de_AT` then `de`, and returned untranslated if none has them.
+#[derive(Debug)]
+pub struct TextDomains {
+    default_domain: String,
+    locales: Vec<String>,
+    bindings: HashMap<String, PathBuf>,
+    cache: Mutex<TranslatorCache>,
+    chains: Mutex<HashMap<String, Arc<FallbackChain>>>,
+}
+
+impl Default for TextDomains {
+    fn default() -> Self {
+        Self {
+            default_domain: String::from("messages"),
+            locales: Vec::new(),
+            bindings: HashMap::new(),
+            cache: Mutex::new(HashMap::new()),
+            chains: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl TextDomains {
+    /// Create an empty registry with the default domain `messages` and no locale.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind a domain to the base directory of its catalogs, like `bindtextdomain`.
+    /// Catalogs of the domain loaded from a previous directory are dropped.
+    pub fn bind_text_domain(&mut self, domain: &str, dir: impl Into<PathBuf>) {
+        self.bindings.insert(String::from(domain), dir.into());
+        self.cache.get_mut().unwrap().remove(domain);
+        self.chains.get_mut().unwrap().remove(domain);
+    }
+
+    /// The base directory a domain is bound to.
+    pub fn text_domain_dir(&self, domain: &str) -> Option<&Path> {
+        self.bindings.get(domain).map(PathBuf::as_path)
+    }
+
+    /// Set the domain used by `gettext` and friends, like `textdomain`.
+    pub fn set_text_domain(&mut self, domain: &str) {
+        self.default_domain = String::from(domain);
+    }
+
+    /// The domain used by `gettext` and friends.
+    pub fn text_domain(&self) -> &str {
+        &self.default_domain
+    }
+
+    /// Set the locale to translate to, e.g. `de_AT`. Catalogs of other locales stay cached.
+    pub fn set_locale(&mut self, locale: &str) {
+        self.locales = vec![String::from(locale)];
+        self.chains.get_mut().unwrap().clear();
+    }
+
+    /// Set the locales to translate to in order of preference, e.g. from `locales_from_env`.
+    pub fn set_locales(&mut self, locales: &[String]) {
+        self.locales = locales.to_vec();
+        self.chains.get_mut().unwrap().clear();
+    }
+
+    /// The locales to translate to in order of preference.
+    pub fn locales(&self) -> &[String] {
+        &self.locales
+    }
+
+    /// Path of the catalog of a domain for a locale: the MO file if it exists,
+    /// otherwise the PO file if it exists. Returns None if the domain is not bound.
+    pub fn catalog_path(&self, domain: &str, locale: &str) -> Option<PathBuf> {
+        let dir = self.bindings.get(domain)?.join(locale).join("LC_MESSAGES");
+        ["mo", "po"]
+            .iter()
+            .map(|extension| dir.join(format!("{}.{}", domain, extension)))
+            .find(|path| path.is_file())
+    }
+
+    /// Get the translator of a domain for a locale, loading its catalog on first use.
+    /// Returns None if the domain is not bound or there is no catalog for the locale.
+    /// The result, including a load error, is cached until the domain is bound again.
+    pub fn translator(
+        &self,
+        domain: &str,
+        locale: &str,
+    ) -> Result<Option<Arc<Translator>>, TextDomainError> {
+        // Keep the lock while loading so that concurrent lookups load a catalog only once
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(cached) = cache.get(domain).and_then(|locales| locales.get(locale)) {
+            return cached.clone();
+        }
+        let translator = match self.catalog_path(domain, locale) {
+            Some(path) => load_translator(&path).map(|translator| Some(Arc::new(translator))),
+            None => Ok(None),
+        };
+        cache
+            .entry(String::from(domain))
+            .or_default()
+            .insert(String::from(locale), translator.clone());
+        translator
+    }
+
+    /// The translators of a domain for the current locales and their fallbacks, e.g. `de_AT`
+    /// and `de`, see `fallback_locales`. Catalogs that cannot be loaded are skipped.
+    /// The chain is built on first use and cached until the locales or the binding change.
+    pub fn fallback_chain(&self, domain: &str) -> Arc<FallbackChain> {
+        let mut chains = self.chains.lock().unwrap();
+        if let Some(chain) = chains.get(domain) {
+            return chain.clone();
+        }
+        let mut chain = FallbackChain::new();
+        for locale in fallback_locales(&self.locales) {
+            if let Ok(Some(translator)) = self.translator(domain, &locale) {
+                chain.push(&locale, translator);
+            }
+        }
+        let chain = Arc::new(chain);
+        chains.insert(String::from(domain), chain.clone());
+        chain
+    }
+
+    /// Translate a message in the default domain, see `Translator::gettext`.
+    pub fn gettext(&self, msgid: &str) -> String {
+        self.dgettext(&self.default_domain, msgid)
+    }
+
+    /// Translate a message with plural forms in the default domain, see `Translator::ngettext`.
+    pub fn ngettext(&self, msgid: &str, msgid_plural: &str, n: u64) -> String {
+        self.dngettext(&self.default_domain, msgid, msgid_plural, n)
+    }
+
+    /// Translate a message in a domain, see `Translator::gettext`.
+    pub fn dgettext(&self, domain: &str, msgid: &str) -> String {
+        String::from(self.fallback_chain(domain).gettext(msgid).text)
+    }
+
+    /// Translate a message in a context in a domain, see `Translator::pgettext`.
+    pub fn dpgettext(&self, domain: &str, msgctxt: &str, msgid: &str) -> String {
+        String::from(self.fallback_chain(domain).pgettext(msgctxt, msgid).text)
+    }
+
+    /// Translate a message with plural forms in a domain, see `Translator::ngettext`.
+    pub fn dngettext(&self, domain: &str, msgid: &str, msgid_plural: &str, n: u64) -> String {
+        String::from(
+            self.fallback_chain(domain)
+                .ngettext(msgid, msgid_plural, n)
+                .text,
+        )
+    }
+
+    /// Translate a message with plural forms in a context in a domain,
+    /// see `Translator::npgettext`.
+    pub fn dnpgettext(
+        &self,
+        domain: &str,
+        msgctxt: &str,
+        msgid: &str,
+        msgid_plural: &str,
+        n: u64,
+    ) -> String {
+        String::from(
+            self.fallback_chain(domain)
+                .npgettext(msgctxt, msgid, msgid_plural, n)
+                .text,
+        )
+    }
+}
+
+/// Load a translator from a MO or PO file, depending on its extension.
+fn load_translator(path: &Path) -> Result<Translator, TextDomainError> {
+    let options = POParseOptions {
+        translated_only: true,
+        ..POParseOptions::new()
+    };
+    Ok(Translator::new(&load_catalog(path, &options)?))
+}
+
+/// Load a catalog from a MO file, or from a PO file with the given options,
+/// depending on its extension.
+pub(super) fn load_catalog(
+    path: &Path,
+    options: &POParseOptions,
+) -> Result<Catalog, TextDomainError> {
+    if path.extension() == Some("po".as_ref()) {
+        Ok(po_file::parse_with_option(path, options)?)
+    } else {
+        Ok(mo_file::parse(path)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_text_domains() {
+        let dir = std::env::temp_dir().join(format!("polib-domains-{}", std::process::id()));
+        let messages = dir.join("fr").join("LC_MESSAGES");
+        std::fs::create_dir_all(&messages).unwrap();
+        std::fs::write(
+            messages.join("app.po"),
+            "msgid \"\"\nmsgstr \"\"\n\"Plural-Forms: nplurals=2; plural=(n > 1);\\n\"\n\nmsgid \"Open\"\nmsgstr \"Ouvrir\"\n\nmsgid \"file\"\nmsgid_plural \"files\"\nmsgstr[0] \"fichier\"\nmsgstr[1] \"fichiers\"\n",
+        )
+        .unwrap();
+        std::fs::write(messages.join("broken.po"), "msgstr \"\"\n").unwrap();
+
+        let mut domains = TextDomains::new();
+        domains.bind_text_domain("app", &dir);
+        domains.bind_text_domain("broken", &dir);
+        domains.set_text_domain("app");
+        assert_eq!(domains.gettext("Open"), "Open");
+
+        domains.set_locale("fr_CA");
+        assert_eq!(domains.gettext("Open"), "Ouvrir");
+        assert_eq!(domains.ngettext("file", "files", 0), "fichier");
+        assert_eq!(domains.dngettext("other", "file", "files", 0), "files");
+        assert!(domains.translator("broken", "fr").is_err());
+        assert_eq!(domains.dgettext("broken", "Open"), "Open");
+        assert!(Arc::ptr_eq(
+            &domains.fallback_chain("app"),
+            &domains.fallback_chain("app")
+        ));
+        let translators: Vec<Arc<Translator>> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| domains.translator("app", "fr").unwrap().unwrap()))
+                .collect();
+            threads.into_iter().map(|t| t.join().unwrap()).collect()
+        });
+        assert!(translators.iter().all(|t| Arc::ptr_eq(t, &translators[0])));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(domains.gettext("Open"), "Ouvrir");
+        assert!(domains.translator("broken", "fr").is_err());
+        domains.set_locale("fr");
+        assert_eq!(domains.gettext("Open"), "Ouvrir");
+        domains.bind_text_domain("app", &dir);
+        assert_eq!(domains.gettext("Open"), "Open");
+    }
+}
@@ -0,0 +1,265 @@
+//! Defines `FallbackChain` struct for looking up translations in several catalogs in order.
+
+use std::sync::Arc;
+
+use super::Translator;
+use crate::catalog::Catalog;
+use crate::metadata::LanguageTag;
+
+/// The codeset of a POSIX locale name like `de_DE.UTF-8@euro`, if any.
+fn codeset(locale: &str) -> Option<&str> {
+    let name = locale.split('@').next().unwrap();
+    name.split_once('.')
+        .map(|(_, codeset)| codeset)
+        .filter(|codeset| !codeset.is_empty())
+}
+
+/// Normalize a codeset like libintl does, e.g. `UTF-8` becomes `utf8` and `8859-1`
+/// becomes `iso88591`.
+fn normalize_codeset(codeset: &str) -> String {
+    let normalized: String = codeset
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if normalized.bytes().all(|c| c.is_ascii_digit()) {
+        format!("iso{}", normalized)
+    } else {
+        normalized
+    }
+}
+
+/// Insert a codeset into a POSIX locale name, before the modifier if any.
+fn with_codeset(locale: &str, codeset: &str) -> String {
+    match locale.split_once('@') {
+        Some((name, modifier)) => format!("{}.{}@{}", name, codeset, modifier),
+        None => format!("{}.{}", locale, codeset),
+    }
+}
+
+/// Locales to look up catalogs for, in order: each locale followed by its fallbacks in
+/// POSIX form, see `LanguageTag::fallbacks`, without duplicates. Invalid locales are dropped.
+/// Like libintl, a locale with a codeset tries each fallback with the codeset as given,
+/// then normalized, then without it: `de_AT.UTF-8`, `de_AT.utf8`, `de_AT`, `de.UTF-8`, ...
+pub fn fallback_locales<S: AsRef<str>>(locales: &[S]) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for locale in locales {
+        let locale = locale.as_ref();
+        let Ok(tag) = LanguageTag::parse(locale) else {
+            continue;
+        };
+        let codesets: Vec<String> = codeset(locale)
+            .map(|codeset| vec![String::from(codeset), normalize_codeset(codeset)])
+            .unwrap_or_default();
+        for fallback in tag.fallbacks() {
+            let fallback = fallback.to_posix();
+            let candidates = codesets
+                .iter()
+                .map(|codeset| with_codeset(&fallback, codeset))
+                .chain([fallback.clone()]);
+            for candidate in candidates {
+                if !result.contains(&candidate) {
+                    result.push(candidate);
+                }
+            }
+        }
+    }
+    result
+}
+
+/// A translated string together with the locale of the catalog that supplied it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Translation<'a> {
+    /// The translation, or the source string if no catalog in the chain has one.
+    pub text: &'a str,
+    /// Locale of the catalog that supplied the translation, None for the source string.
+    pub locale: Option<&'a str>,
+}
+
+/// Translators of several locales consulted in order, e.g. `de_AT`, then `de`, then
+/// the source language, like GNU gettext does for the locales listed in `LANGUAGE`.
+#[derive(Clone, Debug, Default)]
+pub struct FallbackChain {
+    links: Vec<(String, Arc<Translator>)>,
+}
+
+impl FallbackChain {
+    /// Create an empty chain that returns the source strings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a chain for a locale from the catalogs of its fallbacks, see `for_locales`.
+    pub fn for_locale<F>(locale: &str, load: F) -> Self
+    where
+        F: FnMut(&str) -> Option<Catalog>,
+    {
+        Self::for_locales(&[locale], load)
+    }
+
+    /// Create a chain for locales in order of preference from the catalogs of the locales
+    /// and their fallbacks, see `fallback_locales`. `load` is called with each locale and
+    /// returns None if there is no catalog for it.
+    pub fn for_locales<S, F>(locales: &[S], mut load: F) -> Self
+    where
+        S: AsRef<str>,
+        F: FnMut(&str) -> Option<Catalog>,
+    {
+        let mut chain = Self::new();
+        for locale in fallback_locales(locales) {
+            if let Some(catalog) = load(&locale) {
+                chain.push(&locale, Translator::new(&catalog));
+            }
+        }
+        chain
+    }
+
+    /// Append the translator of a locale to the end of the chain.
+    pub fn push(&mut self, locale: &str, translator: impl Into<Arc<Translator>>) {
+        self.links.push((String::from(locale), translator.into()));
+    }
+
+    /// Locales of the chain in lookup order.
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.links.iter().map(|(locale, _)| locale.as_str())
+    }
+
+    /// Number of translators in the chain.
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Is the chain empty?
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    fn resolve<'a, F>(&'a self, find: F, source: &'a str) -> Translation<'a>
+    where
+        F: Fn(&'a Translator) -> Option<&'a str>,
+    {
+        self.links
+            .iter()
+            .find_map(|(locale, translator)| {
+                find(translator).map(|text| Translation {
+                    text,
+                    locale: Some(locale.as_str()),
+                })
+            })
+            .unwrap_or(Translation {
+                text: source,
+                locale: None,
+            })
+    }
+
+    /// Translate a message with the first translator that has it, see `Translator::gettext`.
+    pub fn gettext<'a>(&'a self, msgid: &'a str) -> Translation<'a> {
+        self.resolve(|t| t.find(None, msgid, 0), msgid)
+    }
+
+    /// Translate a message in a context, see `Translator::pgettext`.
+    pub fn pgettext<'a>(&'a self, msgctxt: &'a str, msgid: &'a str) -> Translation<'a> {
+        self.resolve(|t| t.find(Some(msgctxt), msgid, 0), msgid)
+    }
+
+    /// Translate a message with plural forms for n, selecting the form by the plural rules
+    /// of the translator that has it, see `Translator::ngettext`.
+    pub fn ngettext<'a>(
+        &'a self,
+        msgid: &'a str,
+        msgid_plural: &'a str,
+        n: u64,
+    ) -> Translation<'a> {
+        let source = if n == 1 { msgid } else { msgid_plural };
+        self.resolve(|t| t.find(None, msgid, t.plural_rules.evaluate(n)), source)
+    }
+
+    /// Translate a message with plural forms for n in a context, see `ngettext`.
+    pub fn npgettext<'a>(
+        &'a self,
+        msgctxt: &'a str,
+        msgid: &'a str,
+        msgid_plural: &'a str,
+        n: u64,
+    ) -> Translation<'a> {
+        let source = if n == 1 { msgid } else { msgid_plural };
+        self.resolve(
+            |t| t.find(Some(msgctxt), msgid, t.plural_rules.evaluate(n)),
+            source,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::message::Message;
+    use crate::plural::CatalogPluralRules;
+
+    fn catalog(messages: &[(&str, &str)]) -> Catalog {
+        let mut catalog = Catalog::empty();
+        catalog.metadata.plural_rules = CatalogPluralRules::for_language("de").unwrap();
+        for (msgid, msgstr) in messages {
+            catalog.append_or_update(
+                Message::build_singular()
+                    .with_msgid(String::from(*msgid))
+                    .with_msgstr(String::from(*msgstr))
+                    .done(),
+            );
+        }
+        catalog
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        let chain = FallbackChain::for_locale("de_AT.UTF-8", |locale| match locale {
+            "de_AT" => Some(catalog(&[("January", "Jänner")])),
+            "de" => Some(catalog(&[("January", "Januar"), ("March", "März")])),
+            _ => None,
+        });
+        assert_eq!(chain.locales().collect::<Vec<_>>(), vec!["de_AT", "de"]);
+        assert_eq!(
+            chain.gettext("January"),
+            Translation {
+                text: "Jänner",
+                locale: Some("de_AT")
+            }
+        );
+        assert_eq!(chain.gettext("March").locale, Some("de"));
+        assert_eq!(
+            chain.ngettext("May", "Mays", 2),
+            Translation {
+                text: "Mays",
+                locale: None
+            }
+        );
+        assert!(FallbackChain::for_locale("", |_| None).is_empty());
+        assert_eq!(
+            fallback_locales(&["de_AT", "de_CH", "fr"]),
+            vec!["de_AT", "de", "de_CH", "fr"]
+        );
+        assert_eq!(
+            fallback_locales(&["de_AT.UTF-8", "sr_RS.ISO-8859-5@latin"]),
+            vec![
+                "de_AT.UTF-8",
+                "de_AT.utf8",
+                "de_AT",
+                "de.UTF-8",
+                "de.utf8",
+                "de",
+                "sr_RS.ISO-8859-5@latin",
+                "sr_RS.iso88595@latin",
+                "sr_RS@latin",
+                "sr.ISO-8859-5@latin",
+                "sr.iso88595@latin",
+                "sr@latin",
+                "sr_RS.ISO-8859-5",
+                "sr_RS.iso88595",
+                "sr_RS",
+                "sr.ISO-8859-5",
+                "sr.iso88595",
+                "sr",
+            ]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Choose the locales to translate to from environment variables like libintl.
+
+use std::collections::HashMap;
+
+/// Drop the codeset from a POSIX locale name, e.g. `de_DE.UTF-8@euro` becomes `de_DE@euro`.
+fn strip_codeset(locale: &str) -> String {
+    let (name, modifier) = match locale.split_once('@') {
+        Some((name, modifier)) => (name, Some(modifier)),
+        None => (locale, None),
+    };
+    let name = name.split_once('.').map_or(name, |(name, _codeset)| name);
+    match modifier {
+        Some(modifier) => format!("{}@{}", name, modifier),
+        None => String::from(name),
+    }
+}
+
+fn is_c_locale(locale: &str) -> bool {
+    locale == "C" || locale == "POSIX" || locale.starts_with("C.")
+}
+
+/// Locales to translate messages to in order of preference, chosen from environment
+/// variables the way libintl does.
+///
+/// The locale of messages is the first non-empty of `LC_ALL`, `LC_MESSAGES` and `LANG`.
+/// If it is missing, `C` or `POSIX`, messages are not translated and the list is empty.
+/// Otherwise the colon-separated `LANGUAGE` list is used if set, else that locale.
+/// Codesets are stripped, and `C` and `POSIX` entries and duplicates are dropped.
+pub fn locales_from_env_map(env: &HashMap<String, String>) -> Vec<String> {
+    let var = |name: &str| env.get(name).map(String::as_str).filter(|v| !v.is_empty());
+    let locale = match var("LC_ALL")
+        .or_else(|| var("LC_MESSAGES"))
+        .or_else(|| var("LANG"))
+    {
+        Some(locale) if !is_c_locale(locale) => locale,
+        _ => return Vec::new(),
+    };
+    let mut locales: Vec<String> = Vec::new();
+    for entry in var("LANGUAGE").unwrap_or(locale).split(':') {
+        if entry.is_empty() || is_c_locale(entry) {
+            continue;
+        }
+        let entry = strip_codeset(entry);
+        if !locales.contains(&entry) {
+            locales.push(entry);
+        }
+    }
+    locales
+}
+
+/// Locales to translate messages to, chosen from the environment variables of the process.
+/// Variables that are not valid Unicode are ignored. See `locales_from_env_map`.
+pub fn locales_from_env() -> Vec<String> {
+    let env = ["LANGUAGE", "LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| {
+            let value = std::env::var_os(name)?.into_string().ok()?;
+            Some((String::from(*name), value))
+        })
+        .collect();
+    locales_from_env_map(&env)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn locales(vars: &[(&str, &str)]) -> Vec<String> {
+        let env = vars
+            .iter()
+            .map(|(k, v)| (String::from(*k), String::from(*v)))
+            .collect();
+        locales_from_env_map(&env)
+    }
+
+    #[test]
+    fn test_locales_from_env() {
+        assert_eq!(locales(&[("LANG", "de_AT.UTF-8")]), vec!["de_AT"]);
+        assert_eq!(
+            locales(&[("LANG", "de_AT.UTF-8"), ("LC_MESSAGES", "fr_FR.UTF-8@euro")]),
+            vec!["fr_FR@euro"]
+        );
+        assert_eq!(
+            locales(&[
+                ("LC_ALL", "sv_SE.UTF-8"),
+                ("LC_MESSAGES", "fr_FR"),
+                ("LANGUAGE", "nb_NO:C:nn.UTF-8::nb_NO")
+            ]),
+            vec!["nb_NO", "nn"]
+        );
+        assert_eq!(
+            locales(&[("LANG", "C.UTF-8"), ("LANGUAGE", "de")]),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            locales(&[("LC_ALL", ""), ("LANG", "POSIX")]),
+            Vec::<String>::new()
+        );
+        assert_eq!(locales(&[]), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,196 @@
+//! Defines `Translator` struct for looking up translations at runtime.
+
+mod domains;
+mod fallback;
+mod locale;
+mod reload;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::catalog::Catalog;
+use crate::message::MessageKey;
+use crate::mo_file::{self, MOParseError};
+use crate::plural::CatalogPluralRules;
+
+pub use domains::{TextDomainError, TextDomains};
+pub use fallback::{fallback_locales, FallbackChain, Translation};
+pub use locale::{locales_from_env, locales_from_env_map};
+pub use reload::{ReloadWatcher, ReloadableCatalog};
+
+/// Looks up translations with the semantics of the GNU gettext runtime functions.
+///
+/// Only translated, non-fuzzy messages are used. Messages are keyed by msgctxt and msgid
+/// alone, so a plural message is found regardless of its msgid_plural, and is preferred
+/// over a singular message with the same msgid.
+#[derive(Clone, Debug, Default)]
+pub struct Translator {
+    plural_rules: CatalogPluralRules,
+    translations: BTreeMap<MessageKey, Vec<String>>,
+}
+
+impl Translator {
+    /// Create a translator from the translated, non-fuzzy messages of a catalog.
+    pub fn new(catalog: &Catalog) -> Self {
+        let mut translations = BTreeMap::new();
+        // Plural messages go last to replace singular messages with the same msgid
+        for plural in [false, true] {
+            for message in catalog.messages() {
+                if message.is_plural() != plural || !message.is_translated() || message.is_fuzzy() {
+                    continue;
+                }
+                let key = MessageKey::gen(message.msgctxt(), message.msgid(), None);
+                let forms = match message.msgstr_plural() {
+                    Ok(msgstr_plural) => msgstr_plural.clone(),
+                    Err(_) => vec![String::from(message.msgstr().unwrap())],
+                };
+                translations.insert(key, forms);
+            }
+        }
+        Translator {
+            plural_rules: catalog.metadata.plural_rules.clone(),
+            translations,
+        }
+    }
+
+    /// Load a translator from a MO file on disk.
+    pub fn from_mo_file(path: &Path) -> Result<Self, MOParseError> {
+        Ok(Self::new(&mo_file::parse(path)?))
+    }
+
+    /// The plural rules used to select plural forms.
+    pub fn plural_rules(&self) -> &CatalogPluralRules {
+        &self.plural_rules
+    }
+
+    /// Find a plural form of a translation. Like GNU gettext, the first form is returned
+    /// if the translation has fewer forms, e.g. for a message without msgid_plural.
+    fn find(&self, msgctxt: Option<&str>, msgid: &str, form: usize) -> Option<&str> {
+        let forms = self
+            .translations
+            .get(&MessageKey::gen(msgctxt, msgid, None))?;
+        forms.get(form).or(forms.first()).map(String::as_str)
+    }
+
+    /// Translate a message. Returns msgid if no translation is found.
+    pub fn gettext<'a>(&'a self, msgid: &'a str) -> &'a str {
+        self.pgettext_internal(None, msgid)
+    }
+
+    /// Translate a message in a context. Returns msgid if no translation is found.
+    pub fn pgettext<'a>(&'a self, msgctxt: &str, msgid: &'a str) -> &'a str {
+        self.pgettext_internal(Some(msgctxt), msgid)
+    }
+
+    /// Translate a message with plural forms for n, selecting the form by `Plural-Forms`.
+    /// Returns msgid if n is 1 and msgid_plural otherwise if no translation is found.
+    pub fn ngettext<'a>(&'a self, msgid: &'a str, msgid_plural: &'a str, n: u64) -> &'a str {
+        self.npgettext_internal(None, msgid, msgid_plural, n)
+    }
+
+    /// Translate a message with plural forms for n in a context, see `ngettext`.
+    pub fn npgettext<'a>(
+        &'a self,
+        msgctxt: &str,
+        msgid: &'a str,
+        msgid_plural: &'a str,
+        n: u64,
+    ) -> &'a str {
+        self.npgettext_internal(Some(msgctxt), msgid, msgid_plural, n)
+    }
+
+    fn pgettext_internal<'a>(&'a self, msgctxt: Option<&str>, msgid: &'a str) -> &'a str {
+        self.find(msgctxt, msgid, 0).unwrap_or(msgid)
+    }
+
+    fn npgettext_internal<'a>(
+        &'a self,
+        msgctxt: Option<&str>,
+        msgid: &'a str,
+        msgid_plural: &'a str,
+        n: u64,
+    ) -> &'a str {
+        self.find(msgctxt, msgid, self.plural_rules.evaluate(n))
+            .unwrap_or(if n == 1 { msgid } else { msgid_plural })
+    }
+}
+
+impl From<&Catalog> for Translator {
+    fn from(catalog: &Catalog) -> Self {
+        Self::new(catalog)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::message::Message;
+
+    fn sample_catalog() -> Catalog {
+        let mut catalog = Catalog::empty();
+        catalog.metadata.plural_rules = CatalogPluralRules::for_language("ru").unwrap();
+        catalog.append_or_update(
+            Message::build_singular()
+                .with_msgid(String::from("Open"))
+                .with_msgstr(String::from("Открыть"))
+                .done(),
+        );
+        catalog.append_or_update(
+            Message::build_singular()
+                .with_msgctxt(String::from("door"))
+                .with_msgid(String::from("Open"))
+                .with_msgstr(String::from("Открытая"))
+                .done(),
+        );
+        catalog.append_or_update(
+            Message::build_plural()
+                .with_msgid(String::from("file"))
+                .with_msgid_plural(String::from("files"))
+                .with_msgstr_plural(vec![
+                    String::from("файл"),
+                    String::from("файла"),
+                    String::from("файлов"),
+                ])
+                .done(),
+        );
+        let mut fuzzy = Message::build_singular()
+            .with_msgid(String::from("Close"))
+            .with_msgstr(String::from("Закрыть"))
+            .done();
+        fuzzy.flags.add_flag("fuzzy");
+        catalog.append_or_update(fuzzy);
+        catalog
+    }
+
+    #[test]
+    fn test_gettext() {
+        let translator = Translator::new(&sample_catalog());
+        assert_eq!(translator.gettext("Open"), "Открыть");
+        assert_eq!(translator.pgettext("door", "Open"), "Открытая");
+        assert_eq!(translator.pgettext("window", "Open"), "Open");
+        assert_eq!(translator.gettext("Close"), "Close");
+        assert_eq!(translator.gettext("file"), "файл");
+    }
+
+    #[test]
+    fn test_ngettext() {
+        let translator = Translator::new(&sample_catalog());
+        assert_eq!(translator.ngettext("file", "files", 1), "файл");
+        assert_eq!(translator.ngettext("file", "files", 3), "файла");
+        assert_eq!(translator.ngettext("file", "files", 11), "файлов");
+        assert_eq!(translator.ngettext("file", "other files", 21), "файл");
+        assert_eq!(translator.ngettext("dir", "dirs", 1), "dir");
+        assert_eq!(translator.npgettext("disk", "file", "files", 5), "files");
+        assert_eq!(translator.ngettext("Open", "Opens", 5), "Открыть");
+
+        let mut catalog = sample_catalog();
+        catalog.append_or_update(
+            Message::build_singular()
+                .with_msgid(String::from("file"))
+                .with_msgstr(String::from("досье"))
+                .done(),
+        );
+        let translator = Translator::new(&catalog);
+        assert_eq!(translator.ngettext("file", "files", 3), "файла");
+    }
+}
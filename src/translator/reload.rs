@@ -0,0 +1,196 @@
+//! Defines `ReloadableCatalog` struct for picking up changes to catalog files at runtime.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use super::domains::load_catalog;
+use super::{TextDomainError, Translator};
+use crate::catalog::Catalog;
+use crate::po_file::POParseOptions;
+
+/// Modification time and length of a file, to notice when it changes.
+type FileStamp = (Option<SystemTime>, u64);
+
+fn file_stamp(path: &Path) -> Result<FileStamp, TextDomainError> {
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.modified().ok(), metadata.len()))
+}
+
+#[derive(Debug)]
+struct LoadedCatalog {
+    catalog: Arc<Catalog>,
+    translator: Arc<Translator>,
+    stamp: FileStamp,
+    error: Option<TextDomainError>,
+    /// Stamp of the file when it last failed to load, to skip it until it changes again.
+    failed_stamp: Option<FileStamp>,
+}
+
+/// A thread-safe handle to a catalog loaded from a MO or PO file that is parsed again
+/// when the file changes, so long-running services pick up new translations.
+///
+/// Changes are detected by polling the modification time and size of the file, either
+/// explicitly with `reload_if_changed` or in the background with `watch`. If the changed
+/// file cannot be parsed, the previous version keeps being served and the error is kept
+/// for monitoring until a later reload succeeds.
+#[derive(Debug)]
+pub struct ReloadableCatalog {
+    path: PathBuf,
+    loaded: RwLock<LoadedCatalog>,
+}
+
+impl ReloadableCatalog {
+    /// Load a catalog from a MO or PO file, depending on its extension.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, TextDomainError> {
+        let path = path.into();
+        let stamp = file_stamp(&path)?;
+        let catalog = load_catalog(&path, &POParseOptions::new())?;
+        Ok(Self {
+            loaded: RwLock::new(LoadedCatalog {
+                translator: Arc::new(Translator::new(&catalog)),
+                catalog: Arc::new(catalog),
+                stamp,
+                error: None,
+                failed_stamp: None,
+            }),
+            path,
+        })
+    }
+
+    /// Path of the catalog file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The most recently loaded version of the catalog.
+    pub fn catalog(&self) -> Arc<Catalog> {
+        self.loaded.read().unwrap().catalog.clone()
+    }
+
+    /// A translator for the most recently loaded version of the catalog.
+    pub fn translator(&self) -> Arc<Translator> {
+        self.loaded.read().unwrap().translator.clone()
+    }
+
+    /// The error of the last reload, None if it succeeded or none was attempted.
+    pub fn last_error(&self) -> Option<TextDomainError> {
+        self.loaded.read().unwrap().error.clone()
+    }
+
+    /// Parse the file again if its modification time or size changed since it was last
+    /// loaded. Returns true if a new version was loaded. On error the previous version is
+    /// kept, and the file is not parsed again until it changes.
+    pub fn reload_if_changed(&self) -> Result<bool, TextDomainError> {
+        let stamp = file_stamp(&self.path);
+        let result = stamp.clone().and_then(|stamp| {
+            let loaded = self.loaded.read().unwrap();
+            if stamp == loaded.stamp || Some(stamp) == loaded.failed_stamp {
+                return Ok(None);
+            }
+            drop(loaded);
+            let catalog = load_catalog(&self.path, &POParseOptions::new())?;
+            Ok(Some((stamp, catalog)))
+        });
+        let mut loaded = self.loaded.write().unwrap();
+        match result {
+            Ok(Some((stamp, catalog))) => {
+                *loaded = LoadedCatalog {
+                    translator: Arc::new(Translator::new(&catalog)),
+                    catalog: Arc::new(catalog),
+                    stamp,
+                    error: None,
+                    failed_stamp: None,
+                };
+                Ok(true)
+            }
+            Ok(None) => Ok(false),
+            Err(e) => {
+                loaded.error = Some(e.clone());
+                loaded.failed_stamp = stamp.ok();
+                Err(e)
+            }
+        }
+    }
+
+    /// Call `reload_if_changed` every `interval` on a background thread until the returned
+    /// watcher is dropped.
+    pub fn watch(self: &Arc<Self>, interval: Duration) -> ReloadWatcher {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let catalog = Arc::clone(self);
+        let thread = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let _ = catalog.reload_if_changed();
+            }
+        });
+        ReloadWatcher {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Background thread polling a `ReloadableCatalog` for changes, stopped when dropped.
+#[derive(Debug)]
+pub struct ReloadWatcher {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for ReloadWatcher {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reload_if_changed() {
+        let dir = std::env::temp_dir().join(format!("polib-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.po");
+        let po = |msgstr: &str| {
+            format!(
+                "msgid \"\"\nmsgstr \"Language: fr\\n\"\n\nmsgid \"Open\"\nmsgstr \"{}\"\n",
+                msgstr
+            )
+        };
+        fs::write(&path, po("Ouvrir")).unwrap();
+
+        let catalog = Arc::new(ReloadableCatalog::open(&path).unwrap());
+        assert_eq!(catalog.translator().gettext("Open"), "Ouvrir");
+        assert_eq!(catalog.reload_if_changed(), Ok(false));
+
+        fs::write(&path, "msgid \"Open\"\n").unwrap();
+        assert!(catalog.reload_if_changed().is_err());
+        assert!(catalog.last_error().is_some());
+        assert_eq!(catalog.reload_if_changed(), Ok(false));
+        assert!(catalog.last_error().is_some());
+        assert_eq!(catalog.translator().gettext("Open"), "Ouvrir");
+
+        {
+            let _watcher = catalog.watch(Duration::from_millis(10));
+            fs::write(&path, po("Öffnen")).unwrap();
+            for _ in 0..200 {
+                if catalog.translator().gettext("Open") == "Öffnen" {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+        assert_eq!(catalog.translator().gettext("Open"), "Öffnen");
+        assert_eq!(catalog.catalog().count(), 1);
+        assert_eq!(catalog.last_error(), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,11 +1,13 @@
-use polib::catalog::Catalog;
+use polib::catalog::{Catalog, MessageIdConflict};
+use polib::message::{Message, MessageMutView};
+use polib::metadata::CatalogCharset;
+use polib::mo_file;
 use polib::po_file;
 use std::path::Path;
 
 fn validate_catalog(catalog: &Catalog) {
     assert_eq!(catalog.count(), 4);
-    let mut index: usize = 0;
-    for message in catalog.messages() {
+    for (index, message) in catalog.messages().enumerate() {
         if index == 0 {
             assert!(message.msgctxt().is_none());
             assert_eq!(message.msgid(), "Hello");
@@ -36,15 +38,12 @@ fn validate_catalog(catalog: &Catalog) {
             assert!(!message.is_translated());
             assert_eq!(message.msgstr().unwrap_or("None?"), "");
         }
-
-        index += 1;
     }
 }
 
 fn validate_catalog_sort(catalog: &Catalog) {
     assert_eq!(catalog.count(), 4);
-    let mut index: usize = 0;
-    for message in catalog.messages() {
+    for (index, message) in catalog.messages().enumerate() {
         if index == 0 {
             assert!(message.msgctxt().is_none());
             assert_eq!(message.msgid(), "Book");
@@ -75,15 +74,13 @@ fn validate_catalog_sort(catalog: &Catalog) {
             assert!(!message.is_translated());
             assert_eq!(message.msgstr().unwrap_or("None?"), "");
         }
-
-        index += 1;
     }
 }
 
 fn feed_test_po() -> Vec<u8> {
     let path = Path::new("./tests/sample.po");
     let text = std::fs::read_to_string(path).unwrap();
-    return text.as_bytes().into();
+    text.as_bytes().into()
 }
 
 #[test]
@@ -106,10 +103,190 @@ fn po_round_trip() {
 fn po_round_trip_sort() {
     let catalog = po_file::parse_from_reader(&*feed_test_po()).unwrap();
     let mut writer = std::io::BufWriter::new(Vec::new());
-    po_file::write_sort_by(&catalog, &mut writer, Box::new(|a, b| {
-        a.source().cmp(b.source())
-    })).unwrap();
+    po_file::write_sort_by(
+        &catalog,
+        &mut writer,
+        Box::new(|a, b| a.source().cmp(b.source())),
+    )
+    .unwrap();
     let po_bytes = writer.into_inner().unwrap();
     let catalog_2 = po_file::parse_from_reader(&*po_bytes).unwrap();
     validate_catalog_sort(&catalog_2);
 }
+
+#[test]
+fn mo_round_trip() {
+    let dir = std::env::temp_dir().join(format!("polib-mo-round-trip-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mo_path = dir.join("sample.mo");
+    let po_path = dir.join("sample.po");
+    mo_file::compile_from_po(Path::new("./tests/sample.po"), &mo_path).unwrap();
+    mo_file::decompile_to_po(&mo_path, &po_path).unwrap();
+    let original = po_file::parse(Path::new("./tests/sample.po")).unwrap();
+    let catalog = po_file::parse(&po_path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(catalog.metadata.language, original.metadata.language);
+    assert_eq!(
+        catalog.metadata.plural_rules,
+        original.metadata.plural_rules
+    );
+    assert_eq!(catalog.count(), 3);
+    for message in catalog.messages() {
+        let expected = original
+            .find_message(
+                message.msgctxt(),
+                message.msgid(),
+                message.msgid_plural().ok(),
+            )
+            .unwrap();
+        assert_eq!(message.msgstr().ok(), expected.msgstr().ok());
+        assert_eq!(message.msgstr_plural().ok(), expected.msgstr_plural().ok());
+    }
+}
+
+#[test]
+fn po_round_trip_header() {
+    let input = "# Translation of foo.\n# Copyright (C) 2023 Foo Authors\n#\n#, fuzzy\nmsgid \"\"\nmsgstr \"\"\n\"Language: de\\n\"\n\"Project-Id-Version: foo 1.0\\n\"\n\"X-Generator: Poedit 3.4\\n\"\n\"Plural-Forms: nplurals=2; plural=(n != 1);\\n\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n\nmsgid \"Hello\"\nmsgstr \"Hallo\"\n\n";
+    let catalog = po_file::parse_from_reader(input.as_bytes()).unwrap();
+    assert_eq!(
+        catalog.metadata.comments,
+        "Translation of foo.\nCopyright (C) 2023 Foo Authors\n"
+    );
+    assert!(catalog.metadata.flags.is_fuzzy());
+    let mut writer = std::io::BufWriter::new(Vec::new());
+    po_file::write(&catalog, &mut writer).unwrap();
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(output, format!("\n{}", input));
+
+    let separated = input.replacen("#, fuzzy\n", "\n", 1);
+    let catalog_2 = po_file::parse_from_reader(separated.as_bytes()).unwrap();
+    assert_eq!(catalog_2.metadata.comments, catalog.metadata.comments);
+}
+
+#[test]
+fn po_write_update_revision_date() {
+    let catalog = po_file::parse_from_reader(&*feed_test_po()).unwrap();
+    let options = po_file::POWriteOptions {
+        update_revision_date: true,
+        ..po_file::POWriteOptions::new()
+    };
+    let mut writer = std::io::BufWriter::new(Vec::new());
+    po_file::write_with_option(&catalog, &mut writer, &options).unwrap();
+    let po_bytes = writer.into_inner().unwrap();
+    let catalog_2 = po_file::parse_from_reader(&*po_bytes).unwrap();
+    let revised = catalog_2.metadata.po_revision_date_time().unwrap();
+    assert_eq!(revised.utc_offset, 0);
+    assert_ne!(
+        catalog_2.metadata.po_revision_date,
+        catalog.metadata.po_revision_date
+    );
+}
+
+#[test]
+fn po_write_checks_charset() {
+    let input = "msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=ASCII\\n\"\n\"Plural-Forms: nplurals=1; plural=0;\\n\"\n\nmsgid \"Hello\"\nmsgstr \"Grüß Gott\"\n\n";
+    let mut catalog = po_file::parse_from_reader(input.as_bytes()).unwrap();
+    let options = po_file::POWriteOptions {
+        check_charset: true,
+        ..po_file::POWriteOptions::new()
+    };
+    let mut writer = std::io::BufWriter::new(Vec::new());
+    assert!(po_file::write_with_option(&catalog, &mut writer, &options).is_err());
+    assert!(po_file::write(&catalog, &mut std::io::BufWriter::new(Vec::new())).is_err());
+
+    catalog.metadata.content_type = String::from("text/plain; charset=ISO-8859-1");
+    assert!(po_file::write(&catalog, &mut std::io::BufWriter::new(Vec::new())).is_err());
+    let mut ascii = catalog.clone();
+    ascii
+        .messages_mut()
+        .next()
+        .unwrap()
+        .set_msgstr(String::from("Gruss Gott"))
+        .unwrap();
+    assert!(po_file::write_with_option(&ascii, &mut writer, &options).is_err());
+    po_file::write(&ascii, &mut std::io::BufWriter::new(Vec::new())).unwrap();
+
+    catalog.metadata.set_charset(CatalogCharset::Utf8);
+    po_file::write_with_option(&catalog, &mut writer, &options).unwrap();
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    assert_eq!(output, format!("\n{}", input.replace("ASCII", "UTF-8")));
+}
+
+#[test]
+fn po_write_plural_examples() {
+    let input = "msgid \"\"\nmsgstr \"\"\n\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n\nmsgid \"file\"\nmsgid_plural \"files\"\nmsgstr[0] \"файл\"\nmsgstr[1] \"файла\"\nmsgstr[2] \"файлов\"\n\n";
+    let catalog = po_file::parse_from_reader(input.as_bytes()).unwrap();
+    let options = po_file::POWriteOptions {
+        plural_examples: true,
+        ..po_file::POWriteOptions::new()
+    };
+    let mut writer = std::io::BufWriter::new(Vec::new());
+    po_file::write_with_option(&catalog, &mut writer, &options).unwrap();
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    assert!(output.contains(
+        "# msgstr[0]: 1, 21, 31, 41, 51, 61\n# msgstr[1]: 2, 3, 4, 22, 23, 24\n# msgstr[2]: 0, 5, 6, 7, 8, 9\nmsgid \"file\"\n"
+    ));
+    let catalog_2 = po_file::parse_from_reader(output.as_bytes()).unwrap();
+    assert_eq!(
+        catalog_2
+            .messages()
+            .next()
+            .unwrap()
+            .msgstr_plural()
+            .unwrap(),
+        catalog.messages().next().unwrap().msgstr_plural().unwrap()
+    );
+
+    let mut writer = std::io::BufWriter::new(Vec::new());
+    po_file::write_with_option(&catalog_2, &mut writer, &options).unwrap();
+    assert_eq!(
+        String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+        output
+    );
+    let header_first = format!(
+        "# msgstr[0]: 1
+{}",
+        input
+    );
+    let catalog_3 = po_file::parse_from_reader(header_first.as_bytes()).unwrap();
+    assert!(catalog_3.metadata.comments.is_empty());
+}
+
+#[test]
+fn find_message_by_msgid() {
+    let input = "msgid \"\"\nmsgstr \"\"\n\"Plural-Forms: nplurals=2; plural=(n != 1);\\n\"\n\nmsgid \"Book\"\nmsgid_plural \"Books\"\nmsgstr[0] \"Livre\"\nmsgstr[1] \"Livres\"\n\nmsgid \"Books\"\nmsgstr \"Livres\"\n\nmsgctxt \"shelf\"\nmsgid \"Book\"\nmsgstr \"Livre\"\n\n";
+    let mut catalog = po_file::parse_from_reader(input.as_bytes()).unwrap();
+    assert!(catalog.find_message(None, "Book", None).is_none());
+    let book = catalog.find_message_by_msgid(None, "Book").unwrap();
+    assert_eq!(book.msgid_plural().unwrap(), "Books");
+    assert_eq!(catalog.find_messages_by_msgid(None, "Book").count(), 1);
+    assert!(catalog.find_message_by_msgid(None, "Bo").is_none());
+    assert!(catalog
+        .find_message_by_msgid(Some("shelf"), "Book")
+        .unwrap()
+        .is_singular());
+    assert!(catalog.msgid_conflicts().is_empty());
+
+    catalog.append_or_update(
+        Message::build_singular()
+            .with_msgid(String::from("Book"))
+            .with_msgstr(String::from("Bouquin"))
+            .done(),
+    );
+    assert_eq!(
+        catalog
+            .find_message_by_msgid(None, "Book")
+            .unwrap()
+            .msgstr(),
+        Ok("Bouquin")
+    );
+    assert_eq!(
+        catalog.msgid_conflicts(),
+        vec![MessageIdConflict {
+            msgctxt: None,
+            msgid: String::from("Book"),
+            msgid_plurals: vec![None, Some(String::from("Books"))],
+        }]
+    );
+}